@@ -0,0 +1,442 @@
+use std::{
+    num::{NonZeroU32, NonZeroUsize},
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use clap::Parser;
+use searcher::{
+    matcher::matcher::Algorithm,
+    searcher::search_options::RelativeTo,
+    searcher::{output_format::OutputFormat, type_preset::TypePreset},
+    utils::{
+        match_style::MatchStyle,
+        size::parse_size,
+        time::{parse_duration, parse_time},
+    },
+    EntryType, SortBy,
+};
+
+/// A fast file name fuzzy searcher
+///
+/// Searches directories below the given roots, or the current directory, for
+/// file names matching the query
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    after_help = "Exit status is 0 if anything matched, 1 if nothing did, 2 on errors, 124 if \
+                  --timeout cut the search short and 130 if Ctrl-C did"
+)]
+pub struct Args {
+    /// The query to match file names against
+    #[arg(required_unless_present_any = ["history", "interactive", "type_list", "same_file"])]
+    pub query: Option<String>,
+
+    /// Directories to search, the current directory by default
+    #[arg(value_name = "ROOT")]
+    pub roots: Vec<PathBuf>,
+
+    /// Pick a recent search from the history file to run again
+    #[arg(long, requires = "history_file", conflicts_with_all = ["query", "roots"])]
+    pub history: bool,
+
+    /// List every entry first, then rank them while the query is typed,
+    /// starting from QUERY if given. Enter prints the picked path
+    #[arg(
+        short = 'I',
+        long,
+        conflicts_with_all = ["history", "json", "count", "bench", "stream", "content"]
+    )]
+    pub interactive: bool,
+
+    /// Only rank the files git tracks below the roots, listed with
+    /// git ls-files instead of walking the file system. Roots outside a git
+    /// repository are searched normally
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "interactive", "stream", "bench", "watch", "content",
+            "search_archives"
+        ]
+    )]
+    pub git_tracked: bool,
+
+    /// Record searches in FILE for --history, nothing is recorded without it
+    #[arg(long, value_name = "FILE", env = "SEARCHER_HISTORY_FILE")]
+    pub history_file: Option<PathBuf>,
+
+    /// Read default options from this TOML file instead of
+    /// ~/.config/searcher/config.toml, flags override them
+    #[arg(long, value_name = "FILE", env = "SEARCHER_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// List every path that couldn't be read, not just how many were skipped
+    #[arg(long)]
+    pub verbose: bool,
+
+    /// Print every match as a JSON array instead of opening the interactive
+    /// view. Errors are printed to stderr as {"error": ..., "kind": ...}
+    /// objects, kind being usage for bad input or io
+    #[arg(long)]
+    pub json: bool,
+
+    /// Only print how many entries matched and how long the search took,
+    /// without the live preview or the after-search prompt
+    #[arg(long, conflicts_with_all = ["json", "count", "stream", "output", "format"])]
+    pub bench: bool,
+
+    /// Only print the best match's path, nothing when nothing matched, e.g.
+    /// vim "$(searcher -1 main)"
+    #[arg(
+        short = '1',
+        long,
+        conflicts_with_all = [
+            "json", "count", "bench", "stream", "watch", "interactive",
+            "history", "output"
+        ]
+    )]
+    pub first: bool,
+
+    /// Print the matches as an indented tree of the directories leading to
+    /// them, like tree, instead of opening the interactive view
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "json", "count", "bench", "first", "stream", "watch",
+            "interactive", "history", "output", "format", "print0", "content"
+        ]
+    )]
+    pub tree: bool,
+
+    /// Only print how many entries match
+    #[arg(long, conflicts_with_all = ["json", "stream", "output"])]
+    pub count: bool,
+
+    /// Show each match's score, dimmed, before its path in the interactive view
+    #[arg(long)]
+    pub show_scores: bool,
+
+    /// Make the after-search Show All group matches under their directory
+    #[arg(long)]
+    pub group_by_dir: bool,
+
+    /// Make the after-search Filter match paths by substring instead of
+    /// fuzzy matching
+    #[arg(long)]
+    pub literal_filter: bool,
+
+    /// Print matches as soon as they're found, in discovery order, without the
+    /// live preview or the after-search prompt. With --dedup only the first
+    /// match found of each real file is printed
+    #[arg(long, conflicts_with = "json")]
+    pub stream: bool,
+
+    /// Keep the top matches on screen and search again whenever files are
+    /// created, removed or renamed, until Ctrl-C
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "count", "stream", "bench", "interactive", "output"]
+    )]
+    pub watch: bool,
+
+    /// Also write the matches to FILE, one plain path per line, or as JSON
+    /// with --json in which case nothing is printed
+    #[arg(short = 'o', long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Separate printed paths with NUL instead of newlines, for xargs -0.
+    /// Disables colors
+    #[arg(short = '0', long, conflicts_with_all = ["json", "count", "bench"])]
+    pub print0: bool,
+
+    /// Print each match with a template instead of its path, in plain output
+    /// and Show All. Placeholders are {path}, {name}, {dir}, {score} and {abs}
+    /// for the absolute path, {{ and }} print braces
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["json", "count", "group_by_dir", "show_scores"]
+    )]
+    pub format: Option<OutputFormat>,
+
+    /// Search files ignored by .gitignore, .ignore and global git excludes
+    #[arg(long)]
+    pub no_ignore: bool,
+
+    /// Refuse queries shorter than N characters, 1 by default
+    #[arg(long, value_name = "N")]
+    pub min_query_len: Option<usize>,
+
+    /// The fuzzy matching algorithm: skim (default) or clangd
+    #[arg(long, value_name = "NAME")]
+    pub algorithm: Option<Algorithm>,
+
+    /// Treat the query as a regular expression instead of a fuzzy pattern
+    #[arg(long)]
+    pub regex: bool,
+
+    /// Match the query as a plain substring, which is faster than fuzzy
+    /// matching
+    #[arg(short = 'F', long, conflicts_with = "regex")]
+    pub literal: bool,
+
+    /// Only match names starting with the query, or paths with --match-path,
+    /// instead of fuzzy matching
+    #[arg(long, conflicts_with_all = ["regex", "literal"])]
+    pub prefix: bool,
+
+    /// Only match names equal to the query, like find -name, e.g.
+    /// searcher --exact-name Cargo.toml. A query containing *, ?, [ or { is
+    /// matched as a glob, e.g. '*.rs'
+    #[arg(long, conflicts_with_all = ["regex", "literal", "prefix"])]
+    pub exact_name: bool,
+
+    /// Match names containing any of the query's space separated terms
+    /// instead of all of them, ranked by the best matching one
+    #[arg(long)]
+    pub any: bool,
+
+    /// Match names containing all of the query's terms, the default unless
+    /// the config file sets combine = "any"
+    #[arg(long, conflicts_with = "any")]
+    pub all: bool,
+
+    /// Reject names containing TERM, even when they match the query, e.g.
+    /// searcher config --not test. A regex with --regex. Doesn't affect
+    /// scores, can be repeated
+    #[arg(long = "not", value_name = "TERM")]
+    pub negated: Vec<String>,
+
+    /// Always match the exact case instead of smart-case
+    #[arg(short = 's', long)]
+    pub case_sensitive: bool,
+
+    /// Always ignore case instead of smart-case
+    #[arg(short = 'i', long, conflicts_with = "case_sensitive")]
+    pub ignore_case: bool,
+
+    /// Normalize Unicode and ignore accents, so cafe matches café
+    #[arg(long)]
+    pub fold: bool,
+
+    /// Don't descend more than N directories below each root, 0 only
+    /// searches its immediate children
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// Only match entries at least N directories below each root, 0 being
+    /// its immediate children
+    #[arg(long, value_name = "N")]
+    pub min_depth: Option<usize>,
+
+    /// Skip entries whose path relative to the current directory matches
+    /// the glob, can be repeated
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Read more --exclude globs from FILE, one per line, lines starting
+    /// with # are comments. Can be repeated
+    #[arg(long, value_name = "FILE")]
+    pub ignore_file: Vec<PathBuf>,
+
+    /// Match the lines of files instead of their names, printing
+    /// path:line_number:line for every matching line
+    #[arg(short = 'c', long)]
+    pub content: bool,
+
+    /// Also match the files inside .zip, .tar, .tar.gz and .tgz archives,
+    /// printed as archive.zip!path/in/archive. Reads every archive's listing
+    #[arg(long, conflicts_with = "content")]
+    pub search_archives: bool,
+
+    /// List every name of FILE, its hard links, instead of matching a query.
+    /// Every positional argument is then a root, e.g.
+    /// searcher --same-file notes.txt ~ /mnt/backup
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "history", "interactive", "git_tracked", "stream", "watch",
+            "bench", "content", "search_archives"
+        ]
+    )]
+    pub same_file: Option<PathBuf>,
+
+    /// Show how many lines each matched file has, and include it in --json as
+    /// line_count. Binary files and those above --max-file-size aren't counted
+    #[arg(long, conflicts_with = "content")]
+    pub with_line_count: bool,
+
+    /// Show where matched symlinks point after their path, like
+    /// link -> target, or -> <broken> when it doesn't exist. Included in
+    /// --json as symlink_target
+    #[arg(long, conflicts_with = "content")]
+    pub show_symlink_target: bool,
+
+    /// Skip larger files when searching contents or counting lines, and
+    /// larger tar archives with --search-archives, e.g. 500KB or 10MiB, 50MiB
+    /// by default
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_file_size: Option<u64>,
+
+    /// How many threads to search with. Also read from SEARCHER_THREADS, the
+    /// flag taking precedence over it and both over the config file. Defaults
+    /// to RAYON_NUM_THREADS or the number of CPUs
+    #[arg(short = 'j', long, value_name = "N", env = "SEARCHER_THREADS")]
+    pub threads: Option<NonZeroUsize>,
+
+    /// Perform at most N directory listings and stats per second, across
+    /// every thread, so an NFS mounted root isn't hammered. Unlimited by
+    /// default
+    #[arg(long, value_name = "N")]
+    pub throttle: Option<NonZeroU32>,
+
+    /// Don't descend into directories on other file systems, such as mounted
+    /// network drives or /proc. Only supported on Unix and Windows
+    #[arg(short = 'x', long)]
+    pub one_file_system: bool,
+
+    /// Descend into symlinked directories
+    #[arg(short = 'L', long)]
+    pub follow_symlinks: bool,
+
+    /// How many of the best matches to show before summarizing the rest, 10
+    /// by default
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+
+    /// Discard matches scoring below N
+    #[arg(long, value_name = "N", allow_negative_numbers = true)]
+    pub min_score: Option<i64>,
+
+    /// Discard matches with more than N unmatched characters between their
+    /// first and last matched one, 0 only keeps unbroken runs
+    #[arg(long, value_name = "N")]
+    pub max_gap: Option<usize>,
+
+    /// Stop searching once N matches have been found
+    #[arg(long, value_name = "N")]
+    pub limit: Option<NonZeroUsize>,
+
+    /// Show at most N of the best matches from any one directory, which needs
+    /// every match first so it can't be streamed
+    #[arg(long, value_name = "N", conflicts_with = "stream")]
+    pub max_per_dir: Option<NonZeroUsize>,
+
+    /// Stop searching after this long, e.g. 5s or 2m, and exit with code 124
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub timeout: Option<Duration>,
+
+    /// Rank matches by score (default), path, mtime (newest first), oldest,
+    /// size (largest first) or depth (shallowest first), ties are broken by
+    /// score
+    #[arg(long, value_name = "ORDER")]
+    pub sort_by: Option<SortBy>,
+
+    /// Rank the most recently modified matches first, short for
+    /// --sort-by mtime
+    #[arg(long, conflicts_with_all = ["sort_by", "oldest"])]
+    pub newest: bool,
+
+    /// Rank the least recently modified matches first, short for
+    /// --sort-by oldest
+    #[arg(long, conflicts_with = "sort_by")]
+    pub oldest: bool,
+
+    /// Only match entries of this type: file, dir or any (default)
+    #[arg(short = 't', long = "type", value_name = "TYPE")]
+    pub entry_type: Option<EntryType>,
+
+    /// Only match files with this extension, can be repeated
+    #[arg(short = 'e', long = "ext", value_name = "EXT")]
+    pub extensions: Vec<String>,
+
+    /// Only match files of a named group of extensions, e.g. rust for rs and
+    /// toml, can be repeated and combined with --ext. See --type-list
+    #[arg(long, value_name = "NAME")]
+    pub type_preset: Vec<TypePreset>,
+
+    /// List the names and extensions --type-preset accepts
+    #[arg(long, exclusive = true)]
+    pub type_list: bool,
+
+    /// Only match files at least this large, e.g. 500KB or 10MiB.
+    /// Directories are skipped while a size filter is active
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub min_size: Option<u64>,
+
+    /// Only match files at most this large, e.g. 500KB or 10MiB.
+    /// Directories are skipped while a size filter is active
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_size: Option<u64>,
+
+    /// Only match entries modified since TIME, either a duration ago such as
+    /// 30m, 2h or 7d, or a UTC date such as 2024-01-01
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    pub modified_within: Option<SystemTime>,
+
+    /// Only match entries modified before TIME, either a duration ago such as
+    /// 30m, 2h or 7d, or a UTC date such as 2024-01-01
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    pub modified_before: Option<SystemTime>,
+
+    /// Search hidden files and directories, those whose name starts with a .
+    #[arg(short = 'H', long)]
+    pub hidden: bool,
+
+    /// Match the query against the whole relative path, joined with /,
+    /// instead of just the file name
+    #[arg(short = 'p', long)]
+    pub match_path: bool,
+
+    /// Match the last term against the file name and the others against its
+    /// directory, e.g. `src main` matches src/cli/main.rs
+    #[arg(long, conflicts_with = "match_path")]
+    pub path_terms: bool,
+
+    /// Match file names without their extension, so it doesn't affect scores
+    #[arg(long)]
+    pub stem: bool,
+
+    /// Rank matches higher the closer to the start of the file name they begin
+    #[arg(long)]
+    pub prefer_prefix: bool,
+
+    /// Also match the searched directories themselves, by their name
+    #[arg(long)]
+    pub include_root: bool,
+
+    /// Print paths relative to the searched directory, root (default), or to
+    /// the current directory, cwd, falling back to absolute paths outside it
+    #[arg(long, value_name = "BASE", conflicts_with = "absolute")]
+    pub relative_to: Option<RelativeTo>,
+
+    /// Print absolute paths instead of paths relative to the searched directory
+    #[arg(long)]
+    pub absolute: bool,
+
+    /// With --absolute, resolve symlinked directories in the printed paths
+    #[arg(long, requires = "absolute")]
+    pub canonicalize: bool,
+
+    /// Show each real file once, even when reached through symlinks or
+    /// overlapping directories
+    #[arg(long)]
+    pub dedup: bool,
+
+    /// How to highlight matched characters: a color such as red or blue,
+    /// optionally joined with bold and/or underline, e.g. green+bold, or none.
+    /// Without a color, e.g. underline, the text keeps its own color.
+    /// red+bold by default
+    #[arg(long, value_name = "STYLE", env = "SEARCHER_MATCH_COLOR")]
+    pub match_style: Option<MatchStyle>,
+
+    /// Never print colors. By default colors are only printed when stderr is
+    /// a terminal and NO_COLOR isn't set
+    #[arg(long, conflicts_with = "force_color")]
+    pub no_color: bool,
+
+    /// Print colors even when stderr isn't a terminal
+    #[arg(long)]
+    pub force_color: bool,
+}
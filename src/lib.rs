@@ -0,0 +1,54 @@
+#![allow(clippy::module_inception)]
+
+pub mod matcher {
+    pub mod fold;
+    pub mod matcher;
+}
+
+pub mod searcher {
+    pub mod after_search;
+    pub mod archive;
+    pub mod content;
+    pub mod exclude;
+    pub mod ignore_chain;
+    pub mod interactive;
+    pub mod output_format;
+    pub mod search_match;
+    pub mod search_options;
+    pub mod searcher;
+    pub mod searcher_builder;
+    pub mod top_matches;
+    pub mod traversal_error;
+    pub mod tree;
+    pub mod type_preset;
+    pub mod watch;
+}
+
+pub mod utils {
+    pub mod clear_screen;
+    pub mod clipboard;
+    pub mod config;
+    pub mod device;
+    pub mod display_path;
+    pub mod editor;
+    pub mod file_manager;
+    pub mod file_stats;
+    pub mod format_count;
+    pub mod git;
+    pub mod history;
+    pub mod match_style;
+    pub mod pager;
+    pub mod size;
+    pub mod stdin;
+    pub mod str_ext;
+    pub mod terminal_height;
+    pub mod throttle;
+    pub mod time;
+    pub mod warning;
+}
+
+pub use crate::searcher::search_match::Match;
+pub use crate::searcher::search_options::{EntryType, SearchOptions};
+pub use crate::searcher::searcher::Searcher;
+pub use crate::searcher::searcher_builder::SearcherBuilder;
+pub use crate::searcher::top_matches::SortBy;
@@ -1,37 +1,539 @@
-use rayon::ThreadPoolBuilder;
+use std::{
+    fs,
+    io::{stdin, stdout, IsTerminal, Write},
+    num::{NonZeroU32, NonZeroUsize},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Instant,
+};
 
-mod matcher {
-    pub mod matcher;
+use anyhow::Context;
+use clap::Parser;
+
+use searcher::{
+    matcher::matcher::{CaseMode, Combine, MatchMode},
+    searcher::{
+        interactive::type_to_search, output_format::OutputFormat, tree::render_tree,
+        type_preset::TypePreset,
+    },
+    utils::config::load_config,
+    utils::git::git_tracked_files,
+    utils::history::{pick_from_history, record_history, HistoryEntry},
+    utils::match_style::ColorChoice,
+    utils::stdin::is_readable_stdin,
+    utils::warning::print_warning,
+    Match, SearchOptions, Searcher, SortBy,
+};
+
+use crate::cli::Args;
+
+mod cli;
+
+/// Exit code for a search that found nothing, matching `grep`.
+const NO_MATCHES: u8 = 1;
+
+/// Exit code for a search that failed, matching `grep`.
+const ERROR: u8 = 2;
+
+/// Exit code for a search cut short by `--timeout`, matching `timeout(1)`.
+const TIMED_OUT: u8 = 124;
+
+/// Exit code for a search interrupted by Ctrl-C, matching shells.
+const INTERRUPTED: u8 = 130;
+
+fn main() -> ExitCode {
+    let args = match Args::try_parse() {
+        Ok(args) => args,
+        // Help and version are still printed as usual
+        Err(err) if err.use_stderr() && std::env::args_os().any(|arg| arg == "--json") => {
+            let message = err.to_string();
+            let message = message.lines().next().unwrap_or_default();
+            print_json_error(message.trim_start_matches("error: "), "usage");
+
+            return ExitCode::from(ERROR);
+        }
+        Err(err) => err.exit(),
+    };
+    let json = args.json;
+
+    match run(args) {
+        Ok(code) => code,
+        Err(err) if json => {
+            print_json_error(&format!("{:#}", err), error_kind(&err));
+            ExitCode::from(ERROR)
+        }
+        Err(err) => {
+            eprintln!("Error: {:#}", err);
+            ExitCode::from(ERROR)
+        }
+    }
 }
 
-mod searcher {
-    pub mod after_search;
-    pub mod searcher;
-    pub mod top_matches;
+/// Whether `err` was caused by reading or writing files, `io`, or by bad
+/// input such as an invalid regex or a root that isn't a directory, `usage`.
+fn error_kind(err: &anyhow::Error) -> &'static str {
+    if err.chain().any(|cause| cause.is::<std::io::Error>()) {
+        "io"
+    } else {
+        "usage"
+    }
 }
 
-mod utils {
-    pub mod clear_screen;
-    pub mod str_ext;
+/// Prints an error as a single line JSON object on stderr, for programs
+/// running searcher with --json.
+fn print_json_error(message: &str, kind: &str) {
+    eprintln!(
+        "{}",
+        serde_json::json!({
+            "error": message,
+            "kind": kind,
+        })
+    );
 }
 
-use crate::searcher::searcher::Searcher;
+/// Prints what couldn't be read during the search, as warnings with --json.
+fn report_errors(searcher: &Searcher, json: bool) {
+    if !json {
+        searcher.report_errors();
+        return;
+    }
 
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let current_dir = std::env::current_dir().unwrap();
+    for line in searcher.error_report() {
+        print_warning(&line, json);
+    }
+}
 
-    let Some(query) = args.get(1) else {
-        eprintln!("No query provided");
-        return;
+/// Every file git tracks below `roots`, or `None` after warning that one of
+/// them isn't in a git repository and has to be searched normally.
+fn git_tracked_paths(roots: &[PathBuf], json: bool) -> anyhow::Result<Option<Vec<PathBuf>>> {
+    let mut paths = Vec::new();
+
+    for root in roots {
+        let root = std::path::absolute(root)?;
+
+        match git_tracked_files(&root)? {
+            Some(files) => paths.extend(files),
+            None => {
+                let message = format!(
+                    "{} isn't in a git repository, searching every file instead",
+                    root.display()
+                );
+                print_warning(&message, json);
+                return Ok(None);
+            }
+        }
+    }
+
+    Ok(Some(paths))
+}
+
+fn run(args: Args) -> anyhow::Result<ExitCode> {
+    if args.type_list {
+        for preset in TypePreset::ALL {
+            println!("{}", preset);
+        }
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Flags override the config file, which overrides the defaults. Lists
+    // from both are combined
+    let config = load_config(args.config.as_deref())?;
+
+    let print0 = args.print0 || config.print0;
+    let options = SearchOptions {
+        verbose: args.verbose || config.verbose,
+        respect_gitignore: !args.no_ignore && config.respect_gitignore,
+        min_query_len: args.min_query_len.unwrap_or(config.min_query_len),
+        match_mode: if args.regex {
+            MatchMode::Regex
+        } else if args.literal {
+            MatchMode::Literal
+        } else if args.prefix {
+            MatchMode::Prefix
+        } else if args.exact_name {
+            MatchMode::Exact
+        } else {
+            config.match_mode
+        },
+        combine: if args.any {
+            Combine::Any
+        } else if args.all {
+            Combine::All
+        } else {
+            config.combine
+        },
+        negated: [config.negated, args.negated.clone()].concat(),
+        algorithm: args.algorithm.unwrap_or(config.algorithm),
+        case: if args.case_sensitive {
+            CaseMode::Sensitive
+        } else if args.ignore_case {
+            CaseMode::Insensitive
+        } else {
+            config.case
+        },
+        fold: args.fold || config.fold,
+        max_depth: args.max_depth.or(config.max_depth),
+        min_depth: args.min_depth.or(config.min_depth),
+        exclude: [config.exclude, args.exclude].concat(),
+        ignore_files: [config.ignore_files, args.ignore_file].concat(),
+        search_contents: args.content || config.search_contents,
+        search_archives: args.search_archives || config.search_archives,
+        same_file: args.same_file.clone(),
+        line_counts: args.with_line_count || config.line_counts,
+        symlink_targets: args.show_symlink_target || config.symlink_targets,
+        max_file_size: args.max_file_size.unwrap_or(config.max_file_size),
+        threads: args.threads.map(NonZeroUsize::get).or(config.threads),
+        throttle: args.throttle.map(NonZeroU32::get).or(config.throttle),
+        one_file_system: args.one_file_system || config.one_file_system,
+        follow_symlinks: args.follow_symlinks || config.follow_symlinks,
+        top: args.top.unwrap_or(config.top),
+        min_score: args.min_score.or(config.min_score),
+        max_gap: args.max_gap.or(config.max_gap),
+        limit: args.limit.or(config.limit),
+        max_per_dir: args.max_per_dir.or(config.max_per_dir),
+        timeout: args.timeout,
+        sort_by: if args.newest {
+            SortBy::Mtime
+        } else if args.oldest {
+            SortBy::Oldest
+        } else {
+            args.sort_by.unwrap_or(config.sort_by)
+        },
+        entry_type: args.entry_type.unwrap_or(config.entry_type),
+        extensions: [config.extensions, args.extensions]
+            .concat()
+            .into_iter()
+            .chain(
+                args.type_preset
+                    .iter()
+                    .flat_map(|preset| preset.extensions)
+                    .map(|extension| extension.to_string()),
+            )
+            .collect(),
+        min_size: args.min_size.or(config.min_size),
+        max_size: args.max_size.or(config.max_size),
+        modified_after: args.modified_within,
+        modified_before: args.modified_before,
+        hidden: args.hidden || config.hidden,
+        include_root: args.include_root || config.include_root,
+        match_path: args.match_path || config.match_path,
+        path_terms: args.path_terms || config.path_terms,
+        stem: args.stem || config.stem,
+        prefer_prefix: args.prefer_prefix || config.prefer_prefix,
+        relative_to: args.relative_to.unwrap_or(config.relative_to),
+        absolute: args.absolute || config.absolute,
+        canonicalize: args.canonicalize || config.canonicalize,
+        dedup: args.dedup || config.dedup,
+        match_style: args.match_style.unwrap_or(config.match_style),
+        // Colors would end up inside the NUL separated paths
+        color: if args.no_color || print0 {
+            ColorChoice::Never
+        } else if args.force_color {
+            ColorChoice::Always
+        } else {
+            config.color
+        },
+        show_scores: args.show_scores || config.show_scores,
+        group_by_dir: args.group_by_dir || config.group_by_dir,
+        literal_filter: args.literal_filter || config.literal_filter,
+        stream: args.stream,
+        print0,
+        format: args.format.clone().or(config.format),
+        output: args.output.clone(),
+    };
+    let format = options.format.clone();
+    let terminator = options.terminator();
+
+    // Rank piped in paths, e.g. from `fd`, instead of walking the file system
+    let from_stdin = !args.history
+        && !args.interactive
+        && !args.git_tracked
+        && args.same_file.is_none()
+        && args.roots.is_empty()
+        && is_readable_stdin();
+
+    if args.interactive {
+        let roots = if args.roots.is_empty() {
+            vec![std::env::current_dir()?]
+        } else {
+            args.roots.clone()
+        };
+
+        let listing = Searcher::listing(roots, options)?;
+        let picked = type_to_search(&listing, args.query.clone().unwrap_or_default())?;
+
+        return Ok(match picked {
+            Some(m) => {
+                print!("{}{}", m.full_path.display(), terminator);
+                ExitCode::SUCCESS
+            }
+            None => ExitCode::from(NO_MATCHES),
+        });
+    }
+
+    if args.same_file.is_some() {
+        // There's no query, so the first positional argument is a root too
+        let roots = args
+            .query
+            .iter()
+            .map(PathBuf::from)
+            .chain(args.roots.iter().cloned())
+            .collect::<Vec<PathBuf>>();
+        let roots = if roots.is_empty() {
+            vec![std::env::current_dir()?]
+        } else {
+            roots
+        };
+
+        let searcher = Searcher::listing(roots, options)?;
+        let matches = searcher.collect()?;
+        print_matches(
+            best_only(&matches, args.first),
+            args.count,
+            args.json,
+            format.as_ref(),
+            terminator,
+            args.output.as_deref(),
+        )?;
+        report_errors(&searcher, args.json);
+
+        return Ok(exit_code(&searcher, matches.len(), args.json));
+    }
+
+    let (query, roots) = if args.history {
+        let history_file = args.history_file.as_deref().unwrap();
+
+        match pick_from_history(history_file)? {
+            Some(entry) => (entry.query, entry.roots),
+            None => return Ok(ExitCode::SUCCESS),
+        }
+    } else {
+        let roots = if args.roots.is_empty() {
+            vec![std::env::current_dir()?]
+        } else {
+            args.roots.clone()
+        };
+
+        (args.query.clone().unwrap(), roots)
+    };
+
+    let single_fuzzy_char = query.trim().chars().count() == 1
+        && options.match_mode == MatchMode::Fuzzy
+        && !options.search_contents;
+    let query_warning = (single_fuzzy_char && options.min_query_len <= 1).then_some(
+        "single character queries match nearly everything, \
+         consider --min-query-len 2 to refuse them",
+    );
+
+    let mut searcher = Searcher::with_roots(roots.clone(), query.clone(), options)?;
+
+    if let Some(history_file) = args.history_file.as_ref().filter(|_| !from_stdin) {
+        // A history that can't be written shouldn't stop the search
+        let roots = roots
+            .iter()
+            .map(std::path::absolute)
+            .collect::<std::io::Result<Vec<PathBuf>>>()?;
+
+        if let Err(err) = record_history(history_file, HistoryEntry { query, roots }) {
+            print_warning(&format!("{:#}", err), args.json);
+        }
+    }
+
+    ctrlc::set_handler(searcher.interrupt_handler())?;
+
+    let listed = if from_stdin {
+        // Relative paths are taken from the current directory, the only root
+        let current_dir = std::env::current_dir()?;
+        let paths = stdin()
+            .lines()
+            .map_while(Result::ok)
+            .filter(|line| !line.is_empty())
+            .map(|line| current_dir.join(line))
+            .collect();
+
+        Some(paths)
+    } else if args.git_tracked {
+        git_tracked_paths(&roots, args.json)?
+    } else {
+        None
     };
-    let verbose = args.contains(&"--verbose".to_string());
 
-    ThreadPoolBuilder::new()
-        .num_threads(14)
-        .build_global()
-        .unwrap();
+    if let Some(message) = query_warning {
+        // The live view and --watch clear the screen before showing their
+        // results, so they print it under them instead
+        let live = listed.is_none()
+            && !(args.bench || args.tree || args.count || args.json || args.first || args.stream);
+
+        if live {
+            searcher.add_warning(message);
+        } else {
+            print_warning(message, args.json);
+        }
+    }
+
+    if let Some(paths) = listed {
+        let matches = searcher.rank(paths);
+        if args.tree {
+            print_tree(&matches, searcher.options());
+            return Ok(exit_code(&searcher, matches.len(), args.json));
+        }
+
+        print_matches(
+            best_only(&matches, args.first),
+            args.count,
+            args.json,
+            format.as_ref(),
+            terminator,
+            args.output.as_deref(),
+        )?;
+
+        return Ok(exit_code(&searcher, matches.len(), args.json));
+    }
+
+    if args.bench {
+        let start = Instant::now();
+        let matches = searcher.collect()?;
+        println!("{} matches in {:?}", matches.len(), start.elapsed());
+        report_errors(&searcher, args.json);
+
+        return Ok(exit_code(&searcher, matches.len(), args.json));
+    }
+
+    if args.tree {
+        let matches = searcher.collect()?;
+        print_tree(&matches, searcher.options());
+        report_errors(&searcher, args.json);
+
+        return Ok(exit_code(&searcher, matches.len(), args.json));
+    }
+
+    if args.count || args.json || args.first {
+        let matches = searcher.collect()?;
+        print_matches(
+            best_only(&matches, args.first),
+            args.count,
+            args.json,
+            format.as_ref(),
+            terminator,
+            args.output.as_deref(),
+        )?;
+        report_errors(&searcher, args.json);
+
+        return Ok(exit_code(&searcher, matches.len(), args.json));
+    }
+
+    if args.stream {
+        let matches = searcher.collect()?;
+
+        if let Some(output) = &args.output {
+            let written = searcher.write_matches(output)?;
+            if !args.json {
+                eprintln!("Wrote {} matches to {}", written, output.display());
+            }
+        }
+        report_errors(&searcher, args.json);
+
+        return Ok(exit_code(&searcher, matches.len(), args.json));
+    }
+
+    if args.watch {
+        searcher.watch()?;
+
+        // Ctrl-C is how watching normally stops, so it isn't reported as an
+        // interrupted search
+        return Ok(if searcher.match_count() == 0 {
+            ExitCode::from(NO_MATCHES)
+        } else {
+            ExitCode::SUCCESS
+        });
+    }
+
+    searcher.search()?;
+
+    Ok(exit_code(&searcher, searcher.match_count(), args.json))
+}
+
+/// Prints `matches` without the interactive UI: their count with --count, a
+/// JSON array with --json, or one plain path, or `format`ted line, per line
+/// or ending in `terminator`. --output writes to a file instead of stdout.
+fn print_matches(
+    matches: &[Match],
+    count: bool,
+    json: bool,
+    format: Option<&OutputFormat>,
+    terminator: char,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    if count {
+        println!("{}", matches.len());
+        return Ok(());
+    }
+
+    let contents = if json {
+        serde_json::to_string(&matches)? + "\n"
+    } else {
+        matches
+            .iter()
+            .map(|m| format!("{}{}", m.plain(format), terminator))
+            .collect()
+    };
+
+    match output {
+        Some(output) => {
+            fs::write(output, contents)
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+            // Only JSON is printed on stderr with --json
+            if !json {
+                eprintln!("Wrote {} matches to {}", matches.len(), output.display());
+            }
+        }
+        // Nothing left to do once the reader has gone away, e.g. `head`
+        None => {
+            let _ = stdout().lock().write_all(contents.as_bytes());
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints `matches` as a tree on stdout, highlighted when stdout is a
+/// terminal.
+fn print_tree(matches: &[Match], options: &SearchOptions) {
+    let style = options
+        .color
+        .enabled_on(stdout().is_terminal())
+        .then_some(options.match_style);
+    let lines = render_tree(matches, style.as_ref());
+
+    let mut out = stdout().lock();
+    for line in lines {
+        // Nothing left to do once the reader has gone away, e.g. `head`
+        if writeln!(out, "{}", line).is_err() {
+            break;
+        }
+    }
+}
+
+/// Just the highest scoring of the sorted `matches` with --first.
+fn best_only(matches: &[Match], first: bool) -> &[Match] {
+    if first {
+        &matches[..matches.len().min(1)]
+    } else {
+        matches
+    }
+}
 
-    let searcher = Searcher::new(current_dir.clone(), query.to_string(), verbose);
-    searcher.search(&current_dir.clone()).unwrap();
+fn exit_code(searcher: &Searcher, match_count: usize, json: bool) -> ExitCode {
+    if searcher.is_cancelled() {
+        print_warning("search interrupted, results are partial", json);
+        ExitCode::from(INTERRUPTED)
+    } else if searcher.timed_out() {
+        print_warning("search timed out, results are partial", json);
+        ExitCode::from(TIMED_OUT)
+    } else if match_count == 0 {
+        ExitCode::from(NO_MATCHES)
+    } else {
+        ExitCode::SUCCESS
+    }
 }
@@ -0,0 +1,95 @@
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Text with accents folded away, e.g. `café` and `cafe\u{301}` both become
+/// `cafe`.
+pub struct Folded {
+    pub text: String,
+    /// The index of the original char each char of `text` came from.
+    origins: Vec<usize>,
+    /// For every original char, whether it is a combining mark.
+    marks: Vec<bool>,
+}
+
+impl Folded {
+    /// Decomposes `text` (NFD) and drops the combining marks.
+    pub fn new(text: &str) -> Self {
+        let mut folded = String::with_capacity(text.len());
+        let mut origins = Vec::with_capacity(text.len());
+        let mut marks = Vec::with_capacity(text.len());
+
+        for (i, c) in text.chars().enumerate() {
+            marks.push(is_combining_mark(c));
+
+            for decomposed in c.nfd().filter(|c| !is_combining_mark(*c)) {
+                folded.push(decomposed);
+                origins.push(i);
+            }
+        }
+
+        Self {
+            text: folded,
+            origins,
+            marks,
+        }
+    }
+
+    /// Maps char indices of the folded text back onto the original text.
+    ///
+    /// Combining marks following a matched char are included, so a
+    /// decomposed accent is highlighted along with its base char.
+    pub fn original_indices(&self, indices: Vec<usize>) -> Vec<usize> {
+        let mut original = Vec::with_capacity(indices.len());
+
+        for index in indices {
+            let Some(&origin) = self.origins.get(index) else {
+                continue;
+            };
+
+            if original.last() == Some(&origin) {
+                continue;
+            }
+            original.push(origin);
+
+            let marks = self.marks[origin + 1..]
+                .iter()
+                .take_while(|is_mark| **is_mark)
+                .count();
+            original.extend(origin + 1..=origin + marks);
+        }
+
+        original
+    }
+}
+
+pub fn fold(text: &str) -> String {
+    Folded::new(text).text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precomposed_and_decomposed_fold_alike() {
+        assert_eq!(Folded::new("caf\u{e9}").text, "cafe");
+        assert_eq!(Folded::new("cafe\u{301}").text, "cafe");
+    }
+
+    #[test]
+    fn precomposed_indices_map_back() {
+        // The folded `e` comes from the single precomposed `é`
+        let folded = Folded::new("caf\u{e9}.txt");
+
+        assert_eq!(folded.original_indices(vec![2, 3]), [2, 3]);
+        assert_eq!(folded.original_indices(vec![4]), [4]);
+    }
+
+    #[test]
+    fn decomposed_indices_include_the_accent() {
+        // `e` and its combining accent are two chars of the original
+        let folded = Folded::new("cafe\u{301}.txt");
+
+        assert_eq!(folded.original_indices(vec![3]), [3, 4]);
+        assert_eq!(folded.original_indices(vec![4]), [5]);
+    }
+}
@@ -1,20 +1,530 @@
+use anyhow::Context;
+use fuzzy_matcher::clangd::ClangdMatcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use globset::{GlobBuilder, GlobMatcher};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use strum_macros::EnumString;
 
+use super::fold::{fold, Folded};
+
+/// How the query is compared against file names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// Fuzzy match the query, in the style of skim / fzf.
+    #[default]
+    Fuzzy,
+    /// Compile the query as a regular expression. Matches are scored by how
+    /// much of the name they cover.
+    Regex,
+    /// Match the query as a plain substring, skipping the fuzzy scorer.
+    /// Scored like [`Regex`](Self::Regex).
+    Literal,
+    /// Only match text starting with the query, highlighting that start.
+    /// Every match scores [`PREFIX_SCORE`].
+    Prefix,
+    /// Only match text equal to the whole query, like `find -name`. A query
+    /// containing `*`, `?`, `[` or `{` is matched as a glob instead. Every
+    /// match scores [`EXACT_SCORE`] and is highlighted in full.
+    Exact,
+}
+
+/// The score of every [`MatchMode::Prefix`] match, the most a regex or
+/// literal match covering the whole name gets.
+pub const PREFIX_SCORE: i64 = 100;
+
+/// The score of every [`MatchMode::Exact`] match.
+pub const EXACT_SCORE: i64 = 100;
+
+/// The algorithm fuzzy matches are scored with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Algorithm {
+    /// skim's v2 algorithm, rewarding consecutive characters and matches
+    /// right after separators or case changes.
+    #[default]
+    Skim,
+    /// The algorithm clangd completes identifiers with. It favours matches at
+    /// the start of words more heavily and penalises skipping characters in
+    /// between, so abbreviations like `mfr` for `my_file.rs` rank higher.
+    Clangd,
+}
+
+/// How the terms of a fuzzy query are combined.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Combine {
+    /// Every term has to match, their scores are summed.
+    #[default]
+    All,
+    /// At least one term has to match, the best scoring one counts.
+    Any,
+}
+
+/// How letter case is compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseMode {
+    /// An all-lowercase query ignores case, a query containing uppercase
+    /// characters only matches the exact case.
+    #[default]
+    Smart,
+    /// Always match the exact case.
+    Sensitive,
+    /// Never consider case.
+    Insensitive,
+}
+
+/// How a [`Matcher`] compares its query, see
+/// [`SearchOptions::matcher_options`](crate::searcher::search_options::SearchOptions::matcher_options).
+#[derive(Debug, Clone, Default)]
+pub struct MatcherOptions {
+    /// Terms a candidate is rejected for containing.
+    pub negated: Vec<String>,
+    pub mode: MatchMode,
+    pub combine: Combine,
+    pub algorithm: Algorithm,
+    pub case: CaseMode,
+    /// Strip accents from the query and candidates before matching.
+    pub fold: bool,
+}
+
+/// Matches file names against a query.
+///
+/// Matching is [smart-case](CaseMode::Smart) by default, so `readme` matches
+/// `README` but `README` doesn't match `readme`. Each term decides on its own.
+///
+/// With `fold` both the query and candidates are Unicode-decomposed and
+/// stripped of accents before matching, so `cafe` matches `café` whether it
+/// is stored precomposed or decomposed. Returned indices always refer to the
+/// original candidate.
+///
+/// In fuzzy mode a query containing whitespace is split into terms that must
+/// all match, so `controller test` only matches names containing both. Their
+/// scores are summed and their indices merged. With [`Combine::Any`] a name
+/// only has to match one of them instead, and is scored and highlighted by
+/// the best one. A regex is always a single term, since whitespace is
+/// meaningful inside a pattern.
+///
+/// [`fmatch_components`](Self::fmatch_components) instead matches the last
+/// term against the file name and the others against its directory.
+///
+/// Candidates matching the query are still rejected when they contain any of
+/// the negated terms, as a substring, or a match of the pattern in regex
+/// mode. Negated terms are only checked after the query matched, don't add to
+/// the score and aren't highlighted. Each is smart-case on its own.
 pub struct Matcher {
-    query: String,
+    terms: Vec<Term>,
+    negated: Vec<Term>,
+    combine: Combine,
     fuzzy_matcher: Box<dyn FuzzyMatcher>,
+    fold: bool,
+}
+
+/// A single term of the query, each of which has to match a candidate.
+enum Term {
+    Fuzzy(String),
+    Regex(Regex),
+    /// A substring, lowercased when case is ignored.
+    Literal {
+        query: String,
+        ignore_case: bool,
+    },
+    /// The start of the text, lowercased when case is ignored.
+    Prefix {
+        query: String,
+        ignore_case: bool,
+    },
+    /// The whole text, lowercased when case is ignored.
+    Exact {
+        query: String,
+        ignore_case: bool,
+    },
+    /// A glob the whole text has to match.
+    Glob(GlobMatcher),
 }
 
 impl Matcher {
-    pub fn new(query: String) -> Self {
+    pub fn new(query: String, options: &MatcherOptions) -> anyhow::Result<Self> {
+        if query.trim().is_empty() {
+            anyhow::bail!("The query is empty");
+        }
+
+        let &MatcherOptions {
+            ref negated,
+            mode,
+            combine,
+            algorithm,
+            case,
+            fold,
+        } = options;
+
+        let query = if fold { self::fold(&query) } else { query };
+        let ignore_case = Self::ignores_case(&query, case);
+
+        let terms = match mode {
+            MatchMode::Fuzzy => query
+                .split_whitespace()
+                .map(|term| Term::Fuzzy(term.to_string()))
+                .collect::<Vec<Term>>(),
+            MatchMode::Regex => {
+                let regex = RegexBuilder::new(&query)
+                    .case_insensitive(ignore_case)
+                    .build()
+                    .with_context(|| format!("Invalid regex `{}`", query))?;
+
+                vec![Term::Regex(regex)]
+            }
+            MatchMode::Exact if query.contains(['*', '?', '[', '{']) => {
+                let glob = GlobBuilder::new(&query)
+                    .case_insensitive(ignore_case)
+                    .literal_separator(true)
+                    .build()
+                    .with_context(|| format!("Invalid glob `{}`", query))?;
+
+                vec![Term::Glob(glob.compile_matcher())]
+            }
+            MatchMode::Literal | MatchMode::Prefix | MatchMode::Exact => {
+                let query = if ignore_case {
+                    query.to_lowercase()
+                } else {
+                    query
+                };
+
+                match mode {
+                    MatchMode::Prefix => vec![Term::Prefix { query, ignore_case }],
+                    MatchMode::Exact => vec![Term::Exact { query, ignore_case }],
+                    _ => vec![Term::Literal { query, ignore_case }],
+                }
+            }
+        };
+
+        let negated = negated
+            .iter()
+            .filter(|term| !term.trim().is_empty())
+            .map(|term| {
+                let term = if fold { self::fold(term) } else { term.clone() };
+                let ignore_case = Self::ignores_case(&term, case);
+
+                if mode == MatchMode::Regex {
+                    let regex = RegexBuilder::new(&term)
+                        .case_insensitive(ignore_case)
+                        .build()
+                        .with_context(|| format!("Invalid regex `{}`", term))?;
+
+                    Ok(Term::Regex(regex))
+                } else {
+                    let query = if ignore_case {
+                        term.to_lowercase()
+                    } else {
+                        term
+                    };
+
+                    Ok(Term::Literal { query, ignore_case })
+                }
+            })
+            .collect::<anyhow::Result<Vec<Term>>>()?;
+
+        let fuzzy_matcher: Box<dyn FuzzyMatcher> = match algorithm {
+            Algorithm::Skim => {
+                let matcher = SkimMatcherV2::default();
+                Box::new(match case {
+                    CaseMode::Smart => matcher.smart_case(),
+                    CaseMode::Sensitive => matcher.respect_case(),
+                    CaseMode::Insensitive => matcher.ignore_case(),
+                })
+            }
+            Algorithm::Clangd => {
+                let matcher = ClangdMatcher::default();
+                Box::new(match case {
+                    CaseMode::Smart => matcher.smart_case(),
+                    CaseMode::Sensitive => matcher.respect_case(),
+                    CaseMode::Insensitive => matcher.ignore_case(),
+                })
+            }
+        };
+
+        Ok(Self {
+            terms,
+            negated,
+            combine,
+            fuzzy_matcher,
+            fold,
+        })
+    }
+
+    /// A matcher without terms, matching every candidate with a score of 0
+    /// and nothing highlighted.
+    pub fn match_all() -> Self {
         Self {
-            query,
-            fuzzy_matcher: Box::<SkimMatcherV2>::default(),
+            terms: Vec::new(),
+            negated: Vec::new(),
+            combine: Combine::All,
+            fuzzy_matcher: Box::new(SkimMatcherV2::default()),
+            fold: false,
+        }
+    }
+
+    fn ignores_case(query: &str, case: CaseMode) -> bool {
+        match case {
+            CaseMode::Smart => !query.chars().any(char::is_uppercase),
+            CaseMode::Sensitive => false,
+            CaseMode::Insensitive => true,
         }
     }
 
     pub fn fmatch(&self, text: &str) -> Option<(i64, Vec<usize>)> {
-        self.fuzzy_matcher.fuzzy_indices(text, &self.query)
+        let found = self.match_folded(&self.terms, text)?;
+
+        (!self.is_negated(text)).then_some(found)
+    }
+
+    /// Matches the last term against `name` and every other term against
+    /// `dir`, so `src main` matches `src/cli/main.rs` but not `main/src.rs`.
+    /// A single term only has to match the name.
+    ///
+    /// The scores are summed and the indices refer to `dir` and `name` joined
+    /// with `/`, or just `name` when `dir` is empty.
+    pub fn fmatch_components(&self, dir: &str, name: &str) -> Option<(i64, Vec<usize>)> {
+        let (name_term, dir_terms) = self.terms.split_last()?;
+
+        let (name_score, name_indices) =
+            self.match_folded(std::slice::from_ref(name_term), name)?;
+
+        if self.is_negated(name) || self.is_negated(dir) {
+            return None;
+        }
+
+        if dir.is_empty() {
+            return dir_terms.is_empty().then_some((name_score, name_indices));
+        }
+
+        let (dir_score, mut indices) = if dir_terms.is_empty() {
+            (0, Vec::new())
+        } else {
+            self.match_folded(dir_terms, dir)?
+        };
+
+        // The name starts after the directory and its separator
+        let offset = dir.chars().count() + 1;
+        indices.extend(name_indices.into_iter().map(|index| index + offset));
+
+        Some((dir_score + name_score, indices))
+    }
+
+    /// Whether `text` contains any negated term.
+    fn is_negated(&self, text: &str) -> bool {
+        if self.negated.is_empty() {
+            return false;
+        }
+
+        let folded;
+        let text = if self.fold {
+            folded = fold(text);
+            folded.as_str()
+        } else {
+            text
+        };
+
+        self.negated
+            .iter()
+            .any(|term| self.match_term(term, text).is_some())
+    }
+
+    fn match_folded(&self, terms: &[Term], text: &str) -> Option<(i64, Vec<usize>)> {
+        if !self.fold {
+            return self.match_text(terms, text);
+        }
+
+        let folded = Folded::new(text);
+        let (score, indices) = self.match_text(terms, &folded.text)?;
+
+        Some((score, folded.original_indices(indices)))
+    }
+
+    fn match_text(&self, terms: &[Term], text: &str) -> Option<(i64, Vec<usize>)> {
+        if self.combine == Combine::Any && !terms.is_empty() {
+            return terms
+                .iter()
+                .filter_map(|term| self.match_term(term, text))
+                .max_by_key(|(score, _)| *score);
+        }
+
+        let mut score = 0;
+        let mut indices = Vec::new();
+
+        for term in terms {
+            let (term_score, term_indices) = self.match_term(term, text)?;
+            score += term_score;
+            indices.extend(term_indices);
+        }
+
+        // Terms can overlap, and each index must only be colored once
+        indices.sort_unstable();
+        indices.dedup();
+
+        Some((score, indices))
+    }
+
+    fn match_term(&self, term: &Term, text: &str) -> Option<(i64, Vec<usize>)> {
+        match term {
+            Term::Fuzzy(query) => self.fuzzy_matcher.fuzzy_indices(text, query),
+            Term::Regex(regex) => Self::regex_match(regex, text),
+            Term::Literal { query, ignore_case } => Self::literal_match(query, *ignore_case, text),
+            Term::Prefix { query, ignore_case } => Self::prefix_match(query, *ignore_case, text),
+            Term::Exact { query, ignore_case } => Self::exact_match(query, *ignore_case, text),
+            Term::Glob(glob) => glob
+                .is_match(text)
+                .then(|| (EXACT_SCORE, (0..text.chars().count()).collect())),
+        }
+    }
+
+    fn exact_match(query: &str, ignore_case: bool, text: &str) -> Option<(i64, Vec<usize>)> {
+        let matches = if ignore_case {
+            text.chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .eq(query.chars())
+        } else {
+            text == query
+        };
+
+        matches.then(|| (EXACT_SCORE, (0..text.chars().count()).collect()))
+    }
+
+    fn prefix_match(query: &str, ignore_case: bool, text: &str) -> Option<(i64, Vec<usize>)> {
+        let len = query.chars().count();
+
+        let matches = if ignore_case {
+            text.chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .take(len)
+                .eq(query.chars())
+        } else {
+            text.starts_with(query)
+        };
+
+        matches.then(|| (PREFIX_SCORE, (0..len).collect()))
+    }
+
+    fn literal_match(query: &str, ignore_case: bool, text: &str) -> Option<(i64, Vec<usize>)> {
+        // Char position of the first occurrence, matching what the fuzzy
+        // matcher returns
+        let start = if !ignore_case {
+            let byte = text.find(query)?;
+            text[..byte].chars().count()
+        } else if text.is_ascii() {
+            // Bytes are chars here, and lowercasing keeps every position
+            text.to_ascii_lowercase().find(query)?
+        } else {
+            let text = text
+                .chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .collect::<Vec<char>>();
+            let query = query.chars().collect::<Vec<char>>();
+
+            if query.is_empty() {
+                0
+            } else {
+                text.windows(query.len())
+                    .position(|window| window == query)?
+            }
+        };
+
+        let indices = (start..start + query.chars().count()).collect::<Vec<usize>>();
+
+        let total = text.chars().count().max(1);
+        let score = (indices.len() * 100 / total) as i64;
+
+        Some((score, indices))
+    }
+
+    fn regex_match(regex: &Regex, text: &str) -> Option<(i64, Vec<usize>)> {
+        let found = regex.find(text)?;
+
+        // Indices are char positions, matching what the fuzzy matcher returns
+        let indices = text
+            .char_indices()
+            .enumerate()
+            .filter(|(_, (byte, _))| found.range().contains(byte))
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+
+        let total = text.chars().count().max(1);
+        let score = (indices.len() * 100 / total) as i64;
+
+        Some((score, indices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(query: &str, case: CaseMode) -> Matcher {
+        let options = MatcherOptions {
+            case,
+            ..MatcherOptions::default()
+        };
+
+        Matcher::new(query.to_string(), &options).unwrap()
+    }
+
+    #[test]
+    fn smart_case_lowercase_ignores_case() {
+        let matcher = matcher("readme", CaseMode::Smart);
+
+        assert!(matcher.fmatch("README").is_some());
+        assert!(matcher.fmatch("readme").is_some());
+    }
+
+    #[test]
+    fn smart_case_uppercase_respects_case() {
+        let matcher = matcher("README", CaseMode::Smart);
+
+        assert!(matcher.fmatch("README").is_some());
+        assert!(matcher.fmatch("readme").is_none());
+    }
+
+    #[test]
+    fn sensitive_and_insensitive_override_smart_case() {
+        assert!(matcher("readme", CaseMode::Sensitive)
+            .fmatch("README")
+            .is_none());
+        assert!(matcher("README", CaseMode::Insensitive)
+            .fmatch("readme")
+            .is_some());
+    }
+
+    #[test]
+    fn indices_refer_to_the_original_case() {
+        let (_, indices) = matcher("readme", CaseMode::Smart)
+            .fmatch("README.md")
+            .unwrap();
+
+        assert_eq!(indices, [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn any_keeps_a_candidate_matching_one_term() {
+        let options = MatcherOptions {
+            combine: Combine::Any,
+            ..MatcherOptions::default()
+        };
+        let any = Matcher::new("readme license".to_string(), &options).unwrap();
+
+        let (score, indices) = any.fmatch("README.md").unwrap();
+        let (readme_score, readme_indices) = matcher("readme", CaseMode::Smart)
+            .fmatch("README.md")
+            .unwrap();
+
+        // Scored and highlighted by the one term that matched
+        assert_eq!(score, readme_score);
+        assert_eq!(indices, readme_indices);
+        assert!(matcher("readme license", CaseMode::Smart)
+            .fmatch("README.md")
+            .is_none());
     }
 }
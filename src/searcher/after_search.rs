@@ -1,12 +1,303 @@
+use std::{cmp::Reverse, collections::BTreeMap, path::PathBuf, str::FromStr};
+
+use inquire::{InquireError, Select, Text};
 use strum_macros::EnumString;
 
+use crate::{
+    matcher::matcher::{MatchMode, Matcher, MatcherOptions},
+    utils::{
+        clear_screen::clear_screen, clipboard::copy_to_clipboard, editor::open_in_editor,
+        file_manager::reveal_in_file_manager, format_count::format_count, match_style::MatchStyle,
+        pager::page, warning::print_warning,
+    },
+};
+
+use super::{
+    search_match::Match,
+    searcher::Searcher,
+    top_matches::{render_matches, score_width},
+};
+
 #[derive(Debug, EnumString)]
 pub enum AfterSearchOption {
     #[strum(serialize = "Show All")]
     ShowAll,
     Filter,
+    #[strum(serialize = "Open In Editor")]
+    OpenInEditor,
+    #[strum(serialize = "Copy Path")]
+    CopyPath,
+    #[strum(serialize = "Copy All")]
+    CopyAll,
+    #[strum(serialize = "Reveal In File Manager")]
+    RevealInFileManager,
+    #[strum(serialize = "Print Path")]
+    PrintPath,
+    Quit,
 }
 
 impl AfterSearchOption {
-    pub const VARIANTS: [&'static str; 2] = ["Show All", "Filter"];
+    pub const VARIANTS: [&'static str; 8] = [
+        "Show All",
+        "Filter",
+        "Open In Editor",
+        "Copy Path",
+        "Copy All",
+        "Reveal In File Manager",
+        "Print Path",
+        "Quit",
+    ];
+}
+
+impl Searcher {
+    fn show_all(&self) {
+        let matches = self.matches.lock().unwrap();
+        let total = matches.len();
+
+        let lines = self.render_rows(&matches);
+        drop(matches);
+
+        eprintln!();
+
+        clear_screen();
+
+        page(&format!("All Matches ({}):", total), &lines);
+    }
+
+    /// Renders `matches` one per row for Show All and Filter, with `format`
+    /// or grouped by directory when asked to.
+    fn render_rows(&self, matches: &[Match]) -> Vec<String> {
+        let style = self.render_style();
+
+        if let Some(format) = &self.options.format {
+            matches.iter().map(|m| format.render(m)).collect()
+        } else if self.options.group_by_dir {
+            Self::group_by_dir(matches, style.as_ref(), self.options.show_scores)
+        } else {
+            render_matches(matches, style.as_ref(), self.options.show_scores)
+        }
+    }
+
+    /// Renders `matches` under a header for each directory, sorted by
+    /// directory and then by score within each.
+    fn group_by_dir(
+        matches: &[Match],
+        style: Option<&MatchStyle>,
+        show_scores: bool,
+    ) -> Vec<String> {
+        let width = score_width(matches);
+        let mut groups: BTreeMap<&str, Vec<&Match>> = BTreeMap::new();
+
+        for m in matches {
+            groups.entry(&m.path[..m.dir_len()]).or_default().push(m);
+        }
+
+        let mut lines = Vec::new();
+
+        for (dir, mut group) in groups {
+            group.sort_by_key(|m| Reverse(m.score));
+
+            lines.push(dir.to_string());
+            lines.extend(group.iter().map(|m| {
+                let path = m.render_from(dir.len(), style);
+
+                if show_scores {
+                    format!("  {} {}", m.render_score(width, style), path)
+                } else {
+                    format!("  {}", path)
+                }
+            }));
+        }
+
+        lines
+    }
+
+    /// Narrows the matches down to those whose display path matches a new
+    /// query, fuzzy ranked like the search itself, or by substring with
+    /// `literal_filter`. What the filter matched is highlighted instead of the
+    /// search query, and the rows are rendered like Show All's.
+    fn filter(&self) -> anyhow::Result<()> {
+        let query = match Text::new("Filter by:").prompt() {
+            Ok(query) => query,
+            // Escape goes back to the menu
+            Err(InquireError::OperationCanceled) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        let matches = self.matches.lock().unwrap();
+
+        // An empty filter keeps every match as the search highlighted it
+        let matches = if query.trim().is_empty() {
+            matches.clone()
+        } else {
+            let mode = if self.options.literal_filter {
+                MatchMode::Literal
+            } else {
+                MatchMode::Fuzzy
+            };
+            let options = MatcherOptions {
+                negated: Vec::new(),
+                mode,
+                ..self.options.matcher_options()
+            };
+            let matcher = Matcher::new(query, &options)?;
+
+            let mut filtered = matches
+                .iter()
+                .filter_map(|m| {
+                    let (score, indices) = matcher.fmatch(&m.path)?;
+
+                    // Highlight what the filter matched across the whole path
+                    let filtered = Match {
+                        score,
+                        indices,
+                        highlight_start: 0,
+                        ..m.clone()
+                    };
+
+                    Some(filtered)
+                })
+                .collect::<Vec<Match>>();
+
+            // Substring matches keep the search's order
+            if !self.options.literal_filter {
+                filtered.sort_by_key(|m| Reverse(m.score));
+            }
+            filtered
+        };
+        let lines = self.render_rows(&matches);
+
+        eprintln!();
+
+        clear_screen();
+
+        page(&format!("Filtered Matches ({}):", matches.len()), &lines);
+
+        Ok(())
+    }
+
+    /// Lets the user pick one of the matches, returning its real path, or
+    /// `None` when there's nothing to pick or the user escapes back to the
+    /// menu.
+    fn select_match(&self) -> anyhow::Result<Option<PathBuf>> {
+        let matches = self.matches.lock().unwrap();
+
+        if matches.is_empty() {
+            eprintln!("No matches to choose from");
+            return Ok(None);
+        }
+
+        let options = matches
+            .iter()
+            .map(|m| m.path.clone())
+            .collect::<Vec<String>>();
+
+        match Select::new("Match:", options).raw_prompt() {
+            Ok(answer) => Ok(Some(matches[answer.index].full_path.clone())),
+            Err(InquireError::OperationCanceled) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn open_in_editor(&self) -> anyhow::Result<()> {
+        if let Some(path) = self.select_match()? {
+            let _in_foreground = self.in_foreground();
+            open_in_editor(&path)?;
+        }
+
+        Ok(())
+    }
+
+    fn copy_path(&self) -> anyhow::Result<()> {
+        if let Some(path) = self.select_match()? {
+            let path = std::path::absolute(path)?;
+            copy_to_clipboard(&path.to_string_lossy())?;
+
+            eprintln!("Copied {}", path.display());
+        }
+
+        Ok(())
+    }
+
+    /// Copies every match's plain path, or `format`ted line, to the clipboard
+    /// one per line.
+    fn copy_all(&self) -> anyhow::Result<()> {
+        let matches = self.matches.lock().unwrap();
+
+        let lines = matches
+            .iter()
+            .map(|m| m.plain(self.options.format.as_ref()))
+            .collect::<Vec<String>>();
+        drop(matches);
+
+        copy_to_clipboard(&lines.join("\n"))?;
+
+        eprintln!("Copied {} matches", format_count(lines.len()));
+
+        Ok(())
+    }
+
+    /// Shows the picked match in the file manager, or prints where it is
+    /// when there's none to launch.
+    fn reveal(&self) -> anyhow::Result<()> {
+        if let Some(path) = self.select_match()? {
+            let path = std::path::absolute(path)?;
+            let _in_foreground = self.in_foreground();
+
+            if !reveal_in_file_manager(&path)? {
+                let dir = path.parent().unwrap_or(&path);
+                eprintln!("No file manager to open, it's in {}", dir.display());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints the picked path to stdout, which only ever receives this so it
+    /// can be captured with `$(searcher ...)`. Returns whether one was picked.
+    fn print_path(&self) -> anyhow::Result<bool> {
+        let Some(path) = self.select_match()? else {
+            return Ok(false);
+        };
+
+        print!("{}{}", path.display(), self.options.terminator());
+
+        Ok(true)
+    }
+
+    /// Prints why an action failed as a warning, so a missing editor or
+    /// clipboard leaves the menu open for the other actions.
+    fn warn_on_error(result: anyhow::Result<()>) {
+        if let Err(err) = result {
+            print_warning(&format!("{:#}", err), false);
+        }
+    }
+
+    /// Offers actions on the matches until the user quits, or a path is
+    /// printed for the calling shell.
+    pub(super) fn after_search(&self) -> anyhow::Result<()> {
+        loop {
+            let answer = Select::new("Options:", AfterSearchOption::VARIANTS.to_vec()).prompt();
+            let answer = match answer {
+                Ok(answer) => AfterSearchOption::from_str(answer).unwrap(),
+                // Escape leaves the menu like Quit does
+                Err(InquireError::OperationCanceled) => return Ok(()),
+                Err(err) => return Err(err.into()),
+            };
+
+            match answer {
+                AfterSearchOption::ShowAll => self.show_all(),
+                AfterSearchOption::Filter => self.filter()?,
+                AfterSearchOption::OpenInEditor => Self::warn_on_error(self.open_in_editor()),
+                AfterSearchOption::CopyPath => Self::warn_on_error(self.copy_path()),
+                AfterSearchOption::CopyAll => Self::warn_on_error(self.copy_all()),
+                AfterSearchOption::RevealInFileManager => Self::warn_on_error(self.reveal()),
+                AfterSearchOption::PrintPath => {
+                    if self.print_path()? {
+                        return Ok(());
+                    }
+                }
+                AfterSearchOption::Quit => return Ok(()),
+            }
+        }
+    }
 }
@@ -0,0 +1,122 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::{Component, Path},
+};
+
+use flate2::read::GzDecoder;
+
+/// The kinds of archive whose entries can be searched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Recognizes an archive by the extension of `path`, compared
+    /// case-insensitively.
+    fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether `path` is named like an archive [`archive_entries`] can list.
+pub fn is_archive(path: &Path) -> bool {
+    ArchiveKind::from_path(path).is_some()
+}
+
+/// Whether listing the archive at `path` reads all of it. A tar archive has
+/// no index, so every entry is read past, and a `.tar.gz` is decompressed
+/// while doing so. Only a zip's central directory is read.
+pub fn lists_whole_archive(path: &Path) -> bool {
+    ArchiveKind::from_path(path).is_some_and(|kind| kind != ArchiveKind::Zip)
+}
+
+/// Lists the files inside the archive at `path` by their path in it, joined
+/// with `/`.
+///
+/// Directories are left out, as are entries with absolute or `..` paths that
+/// couldn't name anything inside the archive. Only the names are kept, but
+/// see [`lists_whole_archive`] for how much of the file is read for them.
+pub fn archive_entries(path: &Path) -> io::Result<Vec<String>> {
+    let Some(kind) = ArchiveKind::from_path(path) else {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "not a supported archive",
+        ));
+    };
+
+    let file = BufReader::new(File::open(path)?);
+
+    let names = match kind {
+        ArchiveKind::Zip => zip_names(file)?,
+        ArchiveKind::Tar => tar_names(file)?,
+        ArchiveKind::TarGz => tar_names(GzDecoder::new(file))?,
+    };
+
+    Ok(names
+        .iter()
+        .filter_map(|name| normalize_entry(name))
+        .collect())
+}
+
+fn zip_names(file: BufReader<File>) -> io::Result<Vec<String>> {
+    let archive = zip::ZipArchive::new(file).map_err(io::Error::other)?;
+
+    let mut names = Vec::new();
+
+    for name in archive.file_names() {
+        let name = name.map_err(io::Error::other)?;
+
+        if !name.ends_with('/') {
+            names.push(name.to_string());
+        }
+    }
+
+    Ok(names)
+}
+
+fn tar_names(reader: impl Read) -> io::Result<Vec<String>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut names = Vec::new();
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+
+        if entry.header().entry_type().is_dir() {
+            continue;
+        }
+
+        names.push(entry.path()?.to_string_lossy().to_string());
+    }
+
+    Ok(names)
+}
+
+/// Rewrites an entry's path as plain names joined with `/`, dropping `.`
+/// components, or `None` when it isn't a relative path inside the archive.
+fn normalize_entry(name: &str) -> Option<String> {
+    let mut parts = Vec::new();
+
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
@@ -0,0 +1,95 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use crate::matcher::matcher::Matcher;
+
+/// How many leading bytes are checked for a NUL byte to detect binary files.
+const BINARY_CHECK_LEN: usize = 8192;
+
+pub struct LineMatch {
+    pub line_number: usize,
+    pub line: String,
+    pub score: i64,
+    pub indices: Vec<usize>,
+}
+
+/// Counts the lines of the file at `path`, `None` for binary files or files
+/// larger than `max_size` bytes. A last line without a newline still counts.
+pub fn count_lines(path: &Path, max_size: u64) -> std::io::Result<Option<usize>> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() > max_size {
+        return Ok(None);
+    }
+
+    let mut reader = BufReader::new(file);
+
+    let head = reader.fill_buf()?;
+    if head[..head.len().min(BINARY_CHECK_LEN)].contains(&0) {
+        return Ok(None);
+    }
+
+    let mut lines = 0;
+    let mut last = b'\n';
+
+    loop {
+        let buffer = reader.fill_buf()?;
+        let Some(&end) = buffer.last() else {
+            break;
+        };
+
+        lines += buffer.iter().filter(|&&byte| byte == b'\n').count();
+        last = end;
+
+        let len = buffer.len();
+        reader.consume(len);
+    }
+
+    if last != b'\n' {
+        lines += 1;
+    }
+
+    Ok(Some(lines))
+}
+
+/// Runs every line of the file at `path` through `matcher`.
+///
+/// The file is streamed line by line so large files are never read fully into
+/// memory. Binary files, detected by a NUL byte near the start of the file,
+/// produce no matches.
+pub fn matching_lines(path: &Path, matcher: &Matcher) -> std::io::Result<Vec<LineMatch>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let head = reader.fill_buf()?;
+    if head[..head.len().min(BINARY_CHECK_LEN)].contains(&0) {
+        return Ok(Vec::new());
+    }
+
+    let mut line_matches = Vec::new();
+    let mut buffer = Vec::new();
+    let mut line_number = 0;
+
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+        line_number += 1;
+
+        let line = String::from_utf8_lossy(&buffer);
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if let Some((score, indices)) = matcher.fmatch(line) {
+            line_matches.push(LineMatch {
+                line_number,
+                line: line.to_string(),
+                score,
+                indices,
+            });
+        }
+    }
+
+    Ok(line_matches)
+}
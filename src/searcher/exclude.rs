@@ -0,0 +1,43 @@
+use std::path::Path;
+
+use anyhow::Context;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+/// Compiles exclude patterns into a single [`GlobSet`].
+///
+/// Patterns are matched against paths relative to the base directory. A
+/// pattern ending in `/**` also excludes the directory itself, so it can be
+/// pruned before it is read.
+pub fn build_exclude_set(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        let glob =
+            Glob::new(pattern).with_context(|| format!("Invalid exclude pattern `{}`", pattern))?;
+        builder.add(glob);
+
+        if let Some(dir) = pattern.strip_suffix("/**") {
+            if !dir.is_empty() {
+                builder.add(Glob::new(dir)?);
+            }
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Reads exclude patterns from a file, one per line. Blank lines and lines
+/// starting with `#` are skipped.
+pub fn read_exclude_file(path: &Path) -> anyhow::Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read ignore file {}", path.display()))?;
+
+    let patterns = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+
+    Ok(patterns)
+}
@@ -0,0 +1,118 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    Match,
+};
+
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// The ignore rules that apply to a directory, from the global git excludes
+/// down to the directory's own `.gitignore` / `.ignore`.
+///
+/// Deeper rules take precedence over shallower ones, and `.ignore` takes
+/// precedence over `.gitignore` in the same directory.
+#[derive(Clone, Default)]
+pub struct IgnoreChain {
+    matchers: Vec<Arc<Gitignore>>,
+}
+
+impl IgnoreChain {
+    /// Builds the chain for `base_dir`, including the global git excludes and
+    /// the ignore files of every parent directory up to the repository root.
+    ///
+    /// Rules that couldn't be parsed are added to `errors`, see
+    /// [`descend`](Self::descend).
+    pub fn new(base_dir: &Path, errors: &mut Vec<(PathBuf, ignore::Error)>) -> Self {
+        let mut chain = Self::default();
+
+        let (global, _) = Gitignore::global();
+        chain.push(global);
+
+        let repo_root = base_dir.ancestors().find(|dir| dir.join(".git").exists());
+
+        if let Some(repo_root) = repo_root.filter(|root| *root != base_dir) {
+            let parents = base_dir
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| *dir != repo_root)
+                .collect::<Vec<&Path>>();
+
+            chain = chain.descend(repo_root, errors);
+
+            for dir in parents.into_iter().rev() {
+                chain = chain.descend(dir, errors);
+            }
+        }
+
+        chain.descend(base_dir, errors)
+    }
+
+    /// Returns the chain for `dir`, a child of the directory this chain
+    /// belongs to.
+    ///
+    /// The rules of an ignore file that parsed are kept even when others in
+    /// it didn't, those are added to `errors` with the file they're in.
+    pub fn descend(&self, dir: &Path, errors: &mut Vec<(PathBuf, ignore::Error)>) -> Self {
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut found = false;
+
+        let mut files = IGNORE_FILES.map(|file_name| dir.join(file_name)).to_vec();
+        if dir.join(".git").is_dir() {
+            files.insert(0, dir.join(".git/info/exclude"));
+        }
+
+        for file in files {
+            match builder.add(&file) {
+                None => found = true,
+                Some(err) if is_not_found(&err) => {}
+                Some(ignore::Error::Partial(partial)) => {
+                    found = true;
+                    errors.extend(partial.into_iter().map(|err| (file.clone(), err)));
+                }
+                Some(err) => {
+                    found = true;
+                    errors.push((file, err));
+                }
+            }
+        }
+
+        let mut chain = self.clone();
+
+        if found {
+            if let Ok(gitignore) = builder.build() {
+                chain.push(gitignore);
+            }
+        }
+
+        chain
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for gitignore in self.matchers.iter().rev() {
+            match gitignore.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+
+        false
+    }
+
+    fn push(&mut self, gitignore: Gitignore) {
+        if !gitignore.is_empty() {
+            self.matchers.push(Arc::new(gitignore));
+        }
+    }
+}
+
+/// Whether `err` means the ignore file doesn't exist, the usual case.
+fn is_not_found(err: &ignore::Error) -> bool {
+    err.io_error()
+        .is_some_and(|err| err.kind() == io::ErrorKind::NotFound)
+}
@@ -0,0 +1,119 @@
+use std::{
+    io::{stderr, Write},
+    path::{PathBuf, MAIN_SEPARATOR_STR},
+};
+
+use anyhow::bail;
+use console::{Key, Term};
+
+use crate::{
+    matcher::matcher::Matcher,
+    utils::{clear_screen::clear_screen, format_count::format_count},
+};
+
+use super::{search_match::Match, searcher::Searcher};
+
+/// Lists every entry below the searcher's roots once, then lets the user
+/// type a query that re-ranks them on every keystroke, like fzf.
+///
+/// The arrow keys move the selection, enter picks it and escape or Ctrl-C
+/// cancel, returning `None`. `listing` should come from
+/// [`Searcher::listing`].
+pub fn type_to_search(listing: &Searcher, query: String) -> anyhow::Result<Option<Match>> {
+    let term = Term::stderr();
+    if !term.is_term() {
+        bail!("Typing a query needs stderr to be a terminal");
+    }
+
+    eprintln!("Indexing...");
+
+    // Directories keep their trailing separator so the type filter still
+    // knows them when ranking
+    let paths = listing
+        .collect()?
+        .into_iter()
+        .map(|m| {
+            let mut path = m.full_path.into_os_string();
+            if m.is_dir {
+                path.push(MAIN_SEPARATOR_STR);
+            }
+            PathBuf::from(path)
+        })
+        .collect::<Vec<PathBuf>>();
+
+    let options = listing.options();
+    let style = options.color.enabled().then_some(options.match_style);
+
+    let mut query = query;
+    let mut ranked = rank(listing, &paths, &query).unwrap_or_default();
+    let mut selected = 0;
+
+    loop {
+        // Leaves a row each for the count and the prompt
+        let rows = term
+            .size_checked()
+            .map_or(options.top, |(rows, _)| (rows as usize).saturating_sub(2))
+            .max(1);
+        let shown = &ranked[..ranked.len().min(rows)];
+        selected = selected.min(shown.len().saturating_sub(1));
+
+        clear_screen();
+        for (index, m) in shown.iter().enumerate() {
+            let cursor = if index == selected { ">" } else { " " };
+            eprintln!("{} {}", cursor, m.render(style.as_ref()));
+        }
+        eprintln!(
+            "  {}/{}",
+            format_count(ranked.len()),
+            format_count(paths.len())
+        );
+        eprint!("> {}", query);
+        let _ = stderr().flush();
+
+        let edited = match term.read_key_raw()? {
+            Key::Enter => {
+                eprintln!();
+                return Ok(ranked.into_iter().nth(selected));
+            }
+            Key::Escape | Key::CtrlC => {
+                eprintln!();
+                return Ok(None);
+            }
+            Key::ArrowUp => {
+                selected = selected.saturating_sub(1);
+                false
+            }
+            Key::ArrowDown => {
+                selected += 1;
+                false
+            }
+            Key::Backspace => query.pop().is_some(),
+            Key::Char(c) if !c.is_control() => {
+                query.push(c);
+                true
+            }
+            _ => false,
+        };
+
+        if edited {
+            if let Some(matches) = rank(listing, &paths, &query) {
+                ranked = matches;
+            }
+            selected = 0;
+        }
+    }
+}
+
+/// Ranks `paths` against `query`, keeping the listing order while it's
+/// empty. `None` while the query is incomplete, such as an unclosed regex.
+fn rank(listing: &Searcher, paths: &[PathBuf], query: &str) -> Option<Vec<Match>> {
+    let options = listing.options();
+
+    let matcher = if query.trim().is_empty() {
+        Matcher::match_all()
+    } else {
+        Matcher::new(query.to_string(), &options.matcher_options()).ok()?
+    };
+
+    Some(listing.rank_with(&matcher, paths.iter().cloned()))
+}
@@ -0,0 +1,186 @@
+use std::{fmt::Write, str::FromStr};
+
+use anyhow::bail;
+use serde::{Deserialize, Deserializer};
+
+use super::search_match::Match;
+
+/// A template each plain result line is printed with, such as
+/// `{score} {path}`.
+///
+/// Placeholders are `{path}` for the display path, `{name}` for the file
+/// name, `{dir}` for the display path's directory, `{score}` and `{abs}` for
+/// the absolute path. `{{` and `}}` print literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputFormat {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Path,
+    Name,
+    Dir,
+    Score,
+    Abs,
+}
+
+impl OutputFormat {
+    /// Fills the template in with the fields of `m`.
+    pub fn render(&self, m: &Match) -> String {
+        let mut line = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => line.push_str(text),
+                Part::Path => line.push_str(&m.path),
+                Part::Name => line.push_str(&m.file_name),
+                Part::Dir => line.push_str(Self::dir(m)),
+                Part::Score => {
+                    let _ = write!(line, "{}", m.score);
+                }
+                Part::Abs => {
+                    let _ = write!(line, "{}", m.full_path.display());
+                }
+            }
+        }
+
+        line
+    }
+
+    /// The directory part of the display path without its trailing
+    /// separator, `.` for a match directly in a `.` root.
+    fn dir(m: &Match) -> &str {
+        let dir = &m.path[..m.dir_len()];
+        let trimmed = dir.trim_end_matches(std::path::is_separator);
+
+        // Keeps the root of an absolute path shown
+        if trimmed.is_empty() {
+            dir
+        } else {
+            trimmed
+        }
+    }
+}
+
+/// Deserialized from the template text, e.g. `"{score} {path}"`.
+impl<'de> Deserialize<'de> for OutputFormat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(template: &str) -> anyhow::Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let Some((name, rest)) = chars.as_str().split_once('}') else {
+                        bail!("Invalid format `{}`, unclosed `{{`", template);
+                    };
+
+                    let part = match name {
+                        "path" => Part::Path,
+                        "name" => Part::Name,
+                        "dir" => Part::Dir,
+                        "score" => Part::Score,
+                        "abs" => Part::Abs,
+                        _ => bail!(
+                            "Invalid format `{}`, unknown placeholder `{{{}}}`",
+                            template,
+                            name
+                        ),
+                    };
+
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(part);
+
+                    chars = rest.chars();
+                }
+                '}' => bail!("Invalid format `{}`, unmatched `}}`", template),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn test_match() -> Match {
+        Match {
+            score: 42,
+            root: "/root".to_string(),
+            relative_path: "src/main.rs".to_string(),
+            depth: 1,
+            file_name: "main.rs".to_string(),
+            indices: Vec::new(),
+            line_number: None,
+            line: None,
+            archive_entry: None,
+            line_count: None,
+            symlink_target: None,
+            full_path: PathBuf::from("/root/src/main.rs"),
+            modified: None,
+            size: None,
+            is_dir: false,
+            path: "./src/main.rs".to_string(),
+            highlight_start: 6,
+        }
+    }
+
+    fn render(template: &str) -> String {
+        template
+            .parse::<OutputFormat>()
+            .unwrap()
+            .render(&test_match())
+    }
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        assert_eq!(render("{score} {path}"), "42 ./src/main.rs");
+        assert_eq!(render("{dir}: {name}"), "./src: main.rs");
+        assert_eq!(render("{{{score}}}"), "{42}");
+    }
+
+    #[test]
+    fn unknown_placeholder_is_rejected() {
+        let err = "{size}".parse::<OutputFormat>().unwrap_err();
+        assert!(err.to_string().contains("unknown placeholder `{size}`"));
+    }
+
+    #[test]
+    fn unbalanced_braces_are_rejected() {
+        for template in ["{path", "path}", "{score} }", "{{path}"] {
+            assert!(template.parse::<OutputFormat>().is_err(), "{}", template);
+        }
+    }
+}
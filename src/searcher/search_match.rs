@@ -0,0 +1,155 @@
+use std::{path::PathBuf, time::SystemTime};
+
+use serde::Serialize;
+
+use crate::utils::{match_style::MatchStyle, str_ext::StrExt};
+
+use super::output_format::OutputFormat;
+
+/// A single search result.
+///
+/// `root` is the absolute directory the match was found under and
+/// `relative_path` is relative to it. `full_path` is the real location of
+/// the entry on disk. `modified` and `size` are only recorded when the sort
+/// order needs them.
+///
+/// `path` is the display path without any ANSI escape codes, highlighting is
+/// deferred to [`render`](Self::render). Only the plain fields are serialized.
+///
+/// When matching full paths `indices` refer to characters of
+/// `relative_path` joined with `/`. In content search mode `line_number` and
+/// `line` hold the matching line and `indices` refer to characters of `line`
+/// instead of `file_name`.
+///
+/// Files found inside archives have `archive_entry` set to their path in
+/// the archive, joined with `/`. Their `full_path` is the archive's and their
+/// `path` separates the two with a `!`, like `./logs.zip!2024/app.log`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Match {
+    pub score: i64,
+    pub root: String,
+    pub relative_path: String,
+    /// How many directories below `root` the entry is, 0 for its direct
+    /// children.
+    pub depth: usize,
+    pub file_name: String,
+    pub indices: Vec<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_entry: Option<String>,
+    /// How many lines the file has, only counted with `line_counts`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_count: Option<usize>,
+    /// Where the symlink points, `<broken>` when that doesn't exist. Only
+    /// read with `symlink_targets`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<String>,
+    #[serde(skip)]
+    pub full_path: PathBuf,
+    #[serde(skip)]
+    pub modified: Option<SystemTime>,
+    #[serde(skip)]
+    pub size: Option<u64>,
+    #[serde(skip)]
+    pub is_dir: bool,
+    #[serde(skip)]
+    pub path: String,
+    /// Byte offset in `path` where the text `indices` refer to starts.
+    #[serde(skip)]
+    pub highlight_start: usize,
+}
+
+impl Match {
+    /// Formats the match for printing, highlighting the matched characters
+    /// with `style`, or plain when it's `None`.
+    pub fn render(&self, style: Option<&MatchStyle>) -> String {
+        self.render_from(0, style)
+    }
+
+    /// Like [`render`](Self::render), but only the part of `path` from byte
+    /// offset `start` onwards.
+    ///
+    /// A recorded `symlink_target` follows the path as ` -> target`, then a
+    /// recorded `line_count`, dimmed unless `style` is `None`. Directories
+    /// are drawn in [`MatchStyle::DIRECTORY`] to set them apart from files,
+    /// also unless `style` is `None`.
+    pub fn render_from(&self, start: usize, style: Option<&MatchStyle>) -> String {
+        let mut path = self.render_path(start, style);
+
+        if let Some(target) = &self.symlink_target {
+            path = format!("{} -> {}", path, target);
+        }
+
+        match (self.line_count, style) {
+            (None, _) => path,
+            (Some(line_count), Some(_)) => format!("{} \x1b[2m({} lines)\x1b[0m", path, line_count),
+            (Some(line_count), None) => format!("{} ({} lines)", path, line_count),
+        }
+    }
+
+    fn render_path(&self, start: usize, style: Option<&MatchStyle>) -> String {
+        let Some(style) = style else {
+            return self.path[start..].to_string();
+        };
+
+        let highlight_start = self.highlight_start.max(start);
+        let (prefix, matched) = self.path.split_at(highlight_start);
+
+        // Indices count chars from `highlight_start`, shift them to wherever
+        // the rendered part of that text begins
+        let skipped = self.path[self.highlight_start..highlight_start]
+            .chars()
+            .count();
+        let indices = self
+            .indices
+            .iter()
+            .filter_map(|index| index.checked_sub(skipped))
+            .collect();
+
+        let rest = if self.is_dir {
+            MatchStyle::DIRECTORY
+        } else {
+            MatchStyle::NONE
+        };
+
+        format!(
+            "{}{}",
+            rest.apply(&prefix[start..]),
+            matched.colorize_matches_over(indices, style, &rest)
+        )
+    }
+
+    /// The line plain output prints for the match, `path` unless it's
+    /// formatted with `format`.
+    pub fn plain(&self, format: Option<&OutputFormat>) -> String {
+        match format {
+            Some(format) => format.render(self),
+            None => self.path.clone(),
+        }
+    }
+
+    /// `score` right aligned to `width` characters, dimmed unless `style` is
+    /// `None`.
+    pub fn render_score(&self, width: usize, style: Option<&MatchStyle>) -> String {
+        let score = format!("{:>width$}", self.score);
+
+        match style {
+            Some(_) => format!("\x1b[2m{}\x1b[0m", score),
+            None => score,
+        }
+    }
+
+    /// Byte length of the directory part of `path` including its trailing
+    /// separator, such as `./src/` of `./src/main.rs`.
+    pub fn dir_len(&self) -> usize {
+        let file_end = match self.line_number {
+            Some(line_number) => self.highlight_start - format!(":{}:", line_number).len(),
+            None => self.path.len(),
+        };
+
+        file_end - self.file_name.len()
+    }
+}
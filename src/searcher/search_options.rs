@@ -0,0 +1,301 @@
+use std::{
+    num::NonZeroUsize,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use serde::Deserialize;
+use strum_macros::EnumString;
+
+use crate::{
+    matcher::matcher::{Algorithm, CaseMode, Combine, MatchMode, MatcherOptions},
+    searcher::{output_format::OutputFormat, top_matches::SortBy},
+    utils::match_style::{ColorChoice, MatchStyle},
+};
+
+/// Which kinds of entries can match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    File,
+    Dir,
+    #[default]
+    Any,
+}
+
+impl EntryType {
+    pub fn allows(self, is_dir: bool) -> bool {
+        match self {
+            EntryType::File => !is_dir,
+            EntryType::Dir => is_dir,
+            EntryType::Any => true,
+        }
+    }
+}
+
+/// What display paths are relative to, unless they're absolute.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RelativeTo {
+    /// The root the entry was found under, shown as `.` when there's only
+    /// one.
+    #[default]
+    Root,
+    /// The current directory. Entries outside it are shown absolute.
+    Cwd,
+}
+
+/// Options controlling how a [`Searcher`](super::searcher::Searcher) walks the
+/// file system and matches entries.
+///
+/// Deserializing fills in missing fields with their defaults, which is how
+/// config files are read. Options that only make sense for a single run,
+/// such as `timeout` or `output`, can't be deserialized.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchOptions {
+    /// List every unreadable path after the search instead of only counting
+    /// them.
+    pub verbose: bool,
+    /// Skip entries ignored by `.gitignore`, `.ignore` and the global git
+    /// excludes, as well as `.git` directories.
+    pub respect_gitignore: bool,
+    /// Refuse queries with fewer characters than this, since very short ones
+    /// match nearly everything. Empty queries are always refused.
+    pub min_query_len: usize,
+    /// How the query is compared against file names.
+    pub match_mode: MatchMode,
+    /// Whether every term of a fuzzy query has to match or any of them.
+    pub combine: Combine,
+    /// Terms a candidate matching the query must not contain, see
+    /// [`Matcher`](crate::matcher::matcher::Matcher).
+    pub negated: Vec<String>,
+    /// The algorithm fuzzy matches are scored with.
+    pub algorithm: Algorithm,
+    /// How letter case is compared, smart-case by default.
+    pub case: CaseMode,
+    /// Normalize Unicode and ignore accents when matching.
+    pub fold: bool,
+    /// How many directory levels below the base directory to descend into.
+    /// `Some(0)` only searches the immediate children of the base directory.
+    pub max_depth: Option<usize>,
+    /// Only match entries at least this many directories below the base
+    /// directory. Shallower directories are still descended into.
+    pub min_depth: Option<usize>,
+    /// Glob patterns, relative to the base directory, of entries to skip.
+    /// Excluded directories are not descended into.
+    pub exclude: Vec<String>,
+    /// Files to read more `exclude` patterns from, one per line with `#`
+    /// comments.
+    pub ignore_files: Vec<PathBuf>,
+    /// Match the lines of files instead of their names.
+    pub search_contents: bool,
+    /// Also match the files inside `.zip`, `.tar`, `.tar.gz` and `.tgz`
+    /// archives by their path in the archive. Archives that can't be read are
+    /// skipped. Not used when searching contents.
+    pub search_archives: bool,
+    /// Instead of matching names, only match the other names of this file,
+    /// its hard links, compared by device and inode, or file index on
+    /// Windows. Entries that can't be opened are skipped.
+    #[serde(skip)]
+    pub same_file: Option<PathBuf>,
+    /// Count the lines of matched files, shown after their path and included
+    /// in JSON. Binary files aren't counted. Not used when searching
+    /// contents.
+    pub line_counts: bool,
+    /// Read where matched symlinks point, shown after their path and
+    /// included in JSON.
+    pub symlink_targets: bool,
+    /// Files larger than this many bytes are skipped when searching contents
+    /// or counting lines, as are larger tar archives when searching archives.
+    pub max_file_size: u64,
+    /// How many threads to search with, defaults to `RAYON_NUM_THREADS` or
+    /// the number of CPUs.
+    pub threads: Option<usize>,
+    /// The most directory listings and stats to perform per second, across
+    /// every thread, to go easy on network file systems. Unlimited when
+    /// `None`.
+    pub throttle: Option<u32>,
+    /// Don't descend into directories on another device than the root, such
+    /// as mounted drives. Only supported on Unix and Windows.
+    pub one_file_system: bool,
+    /// Descend into symlinked directories. Directories that were already
+    /// searched are skipped so symlink cycles terminate.
+    pub follow_symlinks: bool,
+    /// How many of the best matches the interactive view shows before
+    /// summarizing the rest.
+    pub top: usize,
+    /// Discard matches scoring below this.
+    pub min_score: Option<i64>,
+    /// Discard matches with more than this many unmatched characters between
+    /// their first and last matched one, so scattered coincidental matches
+    /// are dropped.
+    pub max_gap: Option<usize>,
+    /// Stop the search once this many matches have been found. The best
+    /// scored of those are still returned first.
+    pub limit: Option<NonZeroUsize>,
+    /// Keep only this many of the best scoring matches from any one
+    /// directory, to spread the results across the tree.
+    pub max_per_dir: Option<NonZeroUsize>,
+    /// Stop the search once it has run this long, keeping the matches found
+    /// so far.
+    #[serde(skip)]
+    pub timeout: Option<Duration>,
+    /// The order matches are ranked in.
+    pub sort_by: SortBy,
+    /// Which kinds of entries can match. Directories are still descended into
+    /// when they can't match themselves.
+    pub entry_type: EntryType,
+    /// Only match files with one of these extensions, compared
+    /// case-insensitively. Matches everything when empty.
+    pub extensions: Vec<String>,
+    /// Only match files of at least this many bytes.
+    pub min_size: Option<u64>,
+    /// Only match files of at most this many bytes.
+    pub max_size: Option<u64>,
+    /// Only match entries modified at or after this time.
+    #[serde(skip)]
+    pub modified_after: Option<SystemTime>,
+    /// Only match entries modified before this time.
+    #[serde(skip)]
+    pub modified_before: Option<SystemTime>,
+    /// Search hidden entries, those whose name starts with a `.`.
+    pub hidden: bool,
+    /// Also match the root directories themselves, not only the entries below
+    /// them. Roots are matched by their name and displayed absolute.
+    pub include_root: bool,
+    /// Match the query against the whole path relative to the base directory,
+    /// joined with `/`, instead of just the file name.
+    pub match_path: bool,
+    /// Match the last query term against the file name and the others
+    /// against its directory, see
+    /// [`Matcher::fmatch_components`](crate::matcher::matcher::Matcher::fmatch_components).
+    pub path_terms: bool,
+    /// Match file names without their extension, e.g. `config` of
+    /// `config.toml`, while still displaying it.
+    pub stem: bool,
+    /// Boost matches that start earlier in the file name, so `read` ranks
+    /// `readme.md` above `thread.rs`.
+    pub prefer_prefix: bool,
+    /// What display paths are relative to.
+    pub relative_to: RelativeTo,
+    /// Display absolute paths instead of paths relative to `relative_to`.
+    pub absolute: bool,
+    /// Resolve symlinks in the directories of absolute display paths, which
+    /// reads the file system for every match.
+    pub canonicalize: bool,
+    /// Keep only the best scoring match of each real file, so files reached
+    /// through symlinks or overlapping roots show up once.
+    pub dedup: bool,
+    /// How matched characters are highlighted in the colorized paths.
+    pub match_style: MatchStyle,
+    /// Whether the interactive view prints colorized paths.
+    pub color: ColorChoice,
+    /// Show each match's score before its path in the interactive view.
+    pub show_scores: bool,
+    /// Group matches under their directory when showing them all.
+    pub group_by_dir: bool,
+    /// Filter matches after the search by substring instead of fuzzy
+    /// matching.
+    pub literal_filter: bool,
+    /// Print each match's plain path as soon as it's found, in discovery order.
+    #[serde(skip)]
+    pub stream: bool,
+    /// Separate plain output with NUL bytes instead of newlines, for
+    /// `xargs -0`.
+    pub print0: bool,
+    /// A template plain output and Show All print each match with instead of
+    /// its display path.
+    pub format: Option<OutputFormat>,
+    /// A file the interactive search also writes its plain results to, one
+    /// path per line.
+    #[serde(skip)]
+    pub output: Option<PathBuf>,
+}
+
+impl SearchOptions {
+    /// The options the query is matched with.
+    pub fn matcher_options(&self) -> MatcherOptions {
+        MatcherOptions {
+            negated: self.negated.clone(),
+            mode: self.match_mode,
+            combine: self.combine,
+            algorithm: self.algorithm,
+            case: self.case,
+            fold: self.fold,
+        }
+    }
+
+    /// What ends each path of plain output.
+    pub fn terminator(&self) -> char {
+        if self.print0 {
+            '\0'
+        } else {
+            '\n'
+        }
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            verbose: false,
+            respect_gitignore: true,
+            min_query_len: 1,
+            match_mode: MatchMode::Fuzzy,
+            combine: Combine::All,
+            negated: Vec::new(),
+            algorithm: Algorithm::Skim,
+            case: CaseMode::Smart,
+            fold: false,
+            max_depth: None,
+            min_depth: None,
+            exclude: Vec::new(),
+            ignore_files: Vec::new(),
+            search_contents: false,
+            search_archives: false,
+            same_file: None,
+            line_counts: false,
+            symlink_targets: false,
+            max_file_size: 50 * 1024 * 1024,
+            threads: None,
+            throttle: None,
+            one_file_system: false,
+            follow_symlinks: false,
+            top: 10,
+            min_score: None,
+            max_gap: None,
+            limit: None,
+            max_per_dir: None,
+            timeout: None,
+            sort_by: SortBy::Score,
+            entry_type: EntryType::Any,
+            extensions: Vec::new(),
+            min_size: None,
+            max_size: None,
+            modified_after: None,
+            modified_before: None,
+            hidden: false,
+            include_root: false,
+            match_path: false,
+            path_terms: false,
+            stem: false,
+            prefer_prefix: false,
+            relative_to: RelativeTo::Root,
+            absolute: false,
+            canonicalize: false,
+            dedup: false,
+            match_style: MatchStyle::default(),
+            color: ColorChoice::Auto,
+            show_scores: false,
+            group_by_dir: false,
+            literal_filter: false,
+            stream: false,
+            print0: false,
+            format: None,
+            output: None,
+        }
+    }
+}
@@ -1,186 +1,1739 @@
 use std::{
-    path::{Path, PathBuf},
-    str::FromStr,
-    sync::{Arc, Mutex},
+    collections::HashSet,
+    ffi::OsStr,
+    fs::DirEntry,
+    io::{stderr, stdout, Write},
+    num::NonZeroUsize,
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
     thread,
+    time::Duration,
 };
 
-use inquire::{Select, Text};
-use rayon::iter::{ParallelBridge, ParallelIterator};
+use anyhow::Context;
+use globset::GlobSet;
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::{Scope, ThreadPool, ThreadPoolBuilder};
+use same_file::Handle;
 
 use crate::{
     matcher::matcher::Matcher,
-    searcher::top_matches::get_top_matches,
-    utils::{clear_screen::clear_screen, str_ext::StrExt},
+    searcher::{
+        archive::{archive_entries, is_archive, lists_whole_archive},
+        top_matches::{
+            cap_matches_per_dir, dedup_key, dedup_matches, get_top_matches, render_matches,
+            sort_matches, DedupKey,
+        },
+        traversal_error::{summarize_errors, Skipped, TraversalError},
+    },
+    utils::file_stats::FileStats,
+    utils::{
+        clear_screen::clear_screen,
+        device::{self, device_id},
+        display_path::{display_path, display_slash_path, slash_path},
+        format_count::format_count,
+        match_style::MatchStyle,
+        terminal_height::terminal_height,
+        throttle::Throttle,
+        warning::print_warning,
+    },
 };
 
-use super::after_search::AfterSearchOption;
+use super::{
+    content::{count_lines, matching_lines},
+    exclude::{build_exclude_set, read_exclude_file},
+    ignore_chain::IgnoreChain,
+    search_match::Match,
+    search_options::{RelativeTo, SearchOptions},
+    searcher_builder::SearcherBuilder,
+};
+
+/// How often the live preview checks for new matches.
+pub(super) const PREVIEW_INTERVAL: Duration = Duration::from_millis(75);
+
+/// The most `prefer_prefix` adds to a score, for a match starting on the
+/// first character of the file name.
+const PREFIX_BONUS: i64 = 30;
+
+/// A directory being searched.
+pub(super) struct Root {
+    /// Where the search starts, made absolute so ignore files above it are
+    /// found.
+    pub(super) path: PathBuf,
+    /// What paths below this root are displayed relative to, `.` when only
+    /// one root is searched.
+    label: PathBuf,
+    /// The device the root is on, only read to stay on it with
+    /// `one_file_system`.
+    device: Option<u64>,
+}
+
+/// An entry found while traversing, with what its directory listing already
+/// tells about it.
+struct Entry {
+    dir_entry: DirEntry,
+    path: PathBuf,
+    is_dir: bool,
+    is_symlink: bool,
+    /// How many directories below its root the entry is.
+    depth: usize,
+}
+
+/// Counts a running search for as long as it's alive.
+pub(super) struct Running<'a>(&'a AtomicUsize);
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Marks a child process, like the editor, as in the foreground for as long
+/// as it's alive.
+pub(super) struct InForeground<'a>(&'a AtomicBool);
+
+impl Drop for InForeground<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
 
 pub struct Searcher {
-    base_dir: PathBuf,
+    pub(super) roots: Vec<Root>,
     matcher: Matcher,
-    verbose: bool,
-    matches: Arc<Mutex<Vec<(i64, String)>>>,
+    pub(super) options: SearchOptions,
+    exclude: GlobSet,
+    /// The current directory when display paths are relative to it.
+    cwd: Option<PathBuf>,
+    /// Lowercased `options.extensions`, without leading dots.
+    extensions: HashSet<String>,
+    pool: ThreadPool,
+    /// The file `options.same_file` names, whose other names are the only
+    /// matches.
+    same_file: Option<Handle>,
+    /// Limits file system operations with `options.throttle`.
+    throttle: Option<Throttle>,
+    /// Canonical paths of the directories searched so far, used to break
+    /// symlink cycles.
+    visited: Mutex<HashSet<PathBuf>>,
+    /// The matches of the current search. Only the collector thread pushes to
+    /// it, workers send it their matches instead so they never wait on the
+    /// lock, and the live preview reads snapshots of it.
+    pub(super) matches: Arc<Mutex<Vec<Match>>>,
+    /// How many matches the current search has sent, counted by the workers
+    /// so `options.limit` is enforced before the collector catches up.
+    found: AtomicUsize,
+    /// The top matches the live preview last drew, compared against on each
+    /// redraw so unchanged results aren't printed again.
     last_printed: Arc<Mutex<Vec<String>>>,
+    /// How many entries the current search has visited.
+    pub(super) scanned: Arc<AtomicUsize>,
+    /// Set to stop the current search early, keeping the matches found so far.
+    cancelled: Arc<AtomicBool>,
+    /// How many searches or watches are running, Ctrl-C stops them instead of
+    /// exiting while one is.
+    running: Arc<AtomicUsize>,
+    /// Set while a child process is in the foreground, which Ctrl-C is left
+    /// to.
+    child_in_foreground: Arc<AtomicBool>,
+    /// Set once `options.limit` matches have been found.
+    limit_reached: AtomicBool,
+    /// Set when the search ran longer than `options.timeout`.
+    timed_out: AtomicBool,
+    /// Paths the last search couldn't read.
+    errors: Mutex<Vec<TraversalError>>,
+    /// Printed under the results of [`search`](Self::search) and
+    /// [`watch`](Self::watch), since they clear the screen first.
+    warnings: Vec<String>,
 }
 
 impl Searcher {
-    pub fn new(base_dir: PathBuf, query: String, verbose: bool) -> Self {
-        Self {
-            base_dir,
+    pub fn new(base_dir: PathBuf, query: String, verbose: bool) -> anyhow::Result<Self> {
+        let options = SearchOptions {
             verbose,
+            ..Default::default()
+        };
+
+        Self::with_options(base_dir, query, options)
+    }
+
+    pub fn with_options(
+        base_dir: PathBuf,
+        query: String,
+        options: SearchOptions,
+    ) -> anyhow::Result<Self> {
+        Self::with_roots(vec![base_dir], query, options)
+    }
+
+    /// Searches each of `roots`, merging and ranking their matches together.
+    /// With more than one root, matches are displayed prefixed by the root
+    /// they were found in instead of `.`.
+    pub fn with_roots(
+        roots: Vec<PathBuf>,
+        query: String,
+        options: SearchOptions,
+    ) -> anyhow::Result<Self> {
+        let query_len = query.trim().chars().count();
+        if query_len > 0 && query_len < options.min_query_len {
+            anyhow::bail!(
+                "The query `{}` is shorter than the minimum of {} characters",
+                query,
+                options.min_query_len
+            );
+        }
+
+        let matcher = Matcher::new(query, &options.matcher_options())?;
+
+        Self::with_matcher(roots, matcher, options)
+    }
+
+    /// A searcher listing every entry of `roots` that passes the filters,
+    /// with a score of 0 and nothing highlighted, e.g. to
+    /// [`rank_with`](Self::rank_with) them later. There's no minimum score
+    /// and contents aren't searched.
+    pub fn listing(roots: Vec<PathBuf>, options: SearchOptions) -> anyhow::Result<Self> {
+        let options = SearchOptions {
+            min_score: None,
+            search_contents: false,
+            ..options
+        };
+
+        Self::with_matcher(roots, Matcher::match_all(), options)
+    }
+
+    fn with_matcher(
+        roots: Vec<PathBuf>,
+        matcher: Matcher,
+        options: SearchOptions,
+    ) -> anyhow::Result<Self> {
+        if options.one_file_system && !device::is_supported() {
+            anyhow::bail!("--one-file-system isn't supported on this platform");
+        }
+
+        let single_root = roots.len() == 1;
+        let roots = roots
+            .into_iter()
+            .map(|root| {
+                if !root.is_dir() {
+                    anyhow::bail!("{} is not a directory", root.display());
+                }
+
+                Ok(Root {
+                    path: std::path::absolute(&root)?,
+                    device: options.one_file_system.then(|| device_id(&root)).flatten(),
+                    label: if single_root {
+                        PathBuf::from(".")
+                    } else {
+                        root
+                    },
+                })
+            })
+            .collect::<anyhow::Result<Vec<Root>>>()?;
+
+        let mut exclude = options.exclude.clone();
+        for ignore_file in &options.ignore_files {
+            exclude.extend(read_exclude_file(ignore_file)?);
+        }
+        let exclude = build_exclude_set(&exclude)?;
+        let extensions = options
+            .extensions
+            .iter()
+            .map(|extension| extension.trim_start_matches('.').to_lowercase())
+            .collect();
+        let cwd = match options.relative_to {
+            RelativeTo::Cwd => Some(std::env::current_dir()?),
+            RelativeTo::Root => None,
+        };
+        // A pool of our own rather than rayon's global one, so the size is
+        // only decided by the options. 0 lets rayon read RAYON_NUM_THREADS
+        // and fall back to the number of CPUs
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(options.threads.unwrap_or(0))
+            .build()?;
+
+        let same_file = options
+            .same_file
+            .as_ref()
+            .map(|path| {
+                if path.is_dir() {
+                    anyhow::bail!(
+                        "{} is a directory, which can't be hard linked",
+                        path.display()
+                    );
+                }
+
+                Handle::from_path(path)
+                    .with_context(|| format!("Failed to open {}", path.display()))
+            })
+            .transpose()?;
+        let throttle = options.throttle.filter(|&rate| rate > 0).map(Throttle::new);
+
+        Ok(Self {
+            roots,
+            options,
+            exclude,
+            cwd,
+            extensions,
+            pool,
+            same_file,
+            throttle,
+            visited: Mutex::new(HashSet::new()),
             matches: Arc::new(Mutex::new(Vec::new())),
-            matcher: Matcher::new(query),
+            found: AtomicUsize::new(0),
+            matcher,
             last_printed: Arc::new(Mutex::new(Vec::new())),
+            scanned: Arc::new(AtomicUsize::new(0)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            running: Arc::new(AtomicUsize::new(0)),
+            child_in_foreground: Arc::new(AtomicBool::new(false)),
+            limit_reached: AtomicBool::new(false),
+            timed_out: AtomicBool::new(false),
+            errors: Mutex::new(Vec::new()),
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Starts configuring a searcher with chainable methods.
+    pub fn builder() -> SearcherBuilder {
+        SearcherBuilder::new()
+    }
+
+    /// Returns a flag that stops the running search when set, e.g. from a
+    /// Ctrl-C handler. The matches found so far are kept.
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancelled)
+    }
+
+    /// A Ctrl-C handler that stops a running search, keeping the matches
+    /// found so far, and exits with 130 on a second Ctrl-C or outside a
+    /// search.
+    ///
+    /// While a child process is in the foreground, e.g. the editor opened
+    /// after the search, Ctrl-C is left to it instead of exiting from under
+    /// it.
+    pub fn interrupt_handler(&self) -> impl FnMut() + Send + 'static {
+        let cancelled = Arc::clone(&self.cancelled);
+        let running = Arc::clone(&self.running);
+        let child_in_foreground = Arc::clone(&self.child_in_foreground);
+
+        move || {
+            if child_in_foreground.load(Ordering::Relaxed) {
+                return;
+            }
+
+            if running.load(Ordering::Relaxed) == 0 || cancelled.swap(true, Ordering::Relaxed) {
+                eprint!("\x1b[0m\x1b[?25h");
+                let _ = stderr().flush();
+                std::process::exit(130);
+            }
         }
     }
 
-    fn check_match(&self, path: &Path, _is_dir: bool) {
-        let base_dir = &self.base_dir;
-        let matcher = &self.matcher;
+    /// Marks a search as running until the returned guard is dropped.
+    pub(super) fn running(&self) -> Running<'_> {
+        self.running.fetch_add(1, Ordering::Relaxed);
+        Running(&self.running)
+    }
 
-        let file_name = path.file_name().unwrap().to_str().unwrap();
+    /// Marks a child process as in the foreground until the returned guard is
+    /// dropped.
+    pub(super) fn in_foreground(&self) -> InForeground<'_> {
+        self.child_in_foreground.store(true, Ordering::Relaxed);
+        InForeground(&self.child_in_foreground)
+    }
 
-        let relative_path = path.strip_prefix(base_dir).unwrap();
-        let parent_dir = relative_path.parent().unwrap().to_str().unwrap();
+    /// Whether the last search was stopped through the
+    /// [`cancel_flag`](Self::cancel_flag), leaving its results partial.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
 
-        if let Some((score, indices)) = matcher.fmatch(file_name) {
-            let colored_name = file_name.colorize_matches(indices);
+    /// Whether the last search was cut short by `options.timeout`, leaving
+    /// partial results.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out.load(Ordering::Relaxed)
+    }
 
-            let formatted_string = format!(".\\{}\\{}", parent_dir, colored_name);
+    /// The paths the last search skipped because they couldn't be read, in
+    /// no particular order.
+    pub fn errors(&self) -> Vec<TraversalError> {
+        self.errors.lock().unwrap().clone()
+    }
 
-            let mut matches = self.matches.lock().unwrap();
+    /// Prints how many paths the last search skipped and why on stderr, and
+    /// every one of them with `verbose`.
+    pub fn report_errors(&self) {
+        for line in self.error_report() {
+            eprintln!("{}", line);
+        }
+    }
+
+    /// Adds a warning to print under the results of [`search`](Self::search)
+    /// and [`watch`](Self::watch).
+    pub fn add_warning(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+
+    /// Prints the warnings added with [`add_warning`](Self::add_warning).
+    pub(super) fn report_warnings(&self) {
+        for message in &self.warnings {
+            print_warning(message, false);
+        }
+    }
+
+    /// The lines [`report_errors`](Self::report_errors) prints: every path
+    /// that couldn't be read with `verbose`, then a summary of them.
+    pub fn error_report(&self) -> Vec<String> {
+        let errors = self.errors.lock().unwrap();
+
+        let mut lines = Vec::new();
+        if self.options.verbose {
+            lines.extend(errors.iter().map(ToString::to_string));
+        }
+        lines.extend(summarize_errors(&errors));
+
+        lines
+    }
+
+    fn record_error(&self, path: &Path, skipped: Skipped, err: &std::io::Error) {
+        let error = TraversalError::new(path.to_path_buf(), skipped, err);
+        self.errors.lock().unwrap().push(error);
+    }
+
+    /// Records the rules of ignore files that couldn't be parsed, so they're
+    /// reported like unreadable paths.
+    fn record_ignore_errors(&self, errors: Vec<(PathBuf, ignore::Error)>) {
+        for (path, err) in errors {
+            let err = std::io::Error::new(std::io::ErrorKind::InvalidData, err);
+            self.record_error(&path, Skipped::IgnoreRule, &err);
+        }
+    }
+
+    pub fn options(&self) -> &SearchOptions {
+        &self.options
+    }
+
+    /// How many matches the last search found.
+    pub fn match_count(&self) -> usize {
+        self.matches.lock().unwrap().len()
+    }
+
+    /// Whether outstanding traversal work should bail out, either because the
+    /// search was cancelled, timed out or enough matches were found.
+    fn should_stop(&self) -> bool {
+        self.is_cancelled() || self.timed_out() || self.limit_reached.load(Ordering::Relaxed)
+    }
+
+    fn has_size_filter(&self) -> bool {
+        self.options.min_size.is_some() || self.options.max_size.is_some()
+    }
+
+    /// Whether the entry passes `min_size` / `max_size`. Directories never
+    /// do while a size filter is active, since their size is ambiguous.
+    fn in_size_range(&self, is_dir: bool, stats: &FileStats) -> bool {
+        if !self.has_size_filter() {
+            return true;
+        }
+
+        let Some(size) = stats.size.filter(|_| !is_dir) else {
+            return false;
+        };
+
+        self.options
+            .min_size
+            .is_none_or(|min_size| size >= min_size)
+            && self
+                .options
+                .max_size
+                .is_none_or(|max_size| size <= max_size)
+    }
+
+    fn has_time_filter(&self) -> bool {
+        self.options.modified_after.is_some() || self.options.modified_before.is_some()
+    }
+
+    /// Whether the entry passes `modified_after` / `modified_before`. Entries
+    /// whose modification time can't be read never do while a time filter is
+    /// active.
+    fn in_time_range(&self, stats: &FileStats) -> bool {
+        if !self.has_time_filter() {
+            return true;
+        }
+
+        let Some(modified) = stats.modified else {
+            return false;
+        };
+
+        self.options
+            .modified_after
+            .is_none_or(|after| modified >= after)
+            && self
+                .options
+                .modified_before
+                .is_none_or(|before| modified < before)
+    }
+
+    fn needs_metadata(&self) -> bool {
+        self.options.sort_by.needs_metadata() || self.has_size_filter() || self.has_time_filter()
+    }
+
+    /// Waits for the throttle, if any, before touching the file system.
+    fn throttle(&self) {
+        if let Some(throttle) = &self.throttle {
+            throttle.wait();
+        }
+    }
+
+    fn file_stats(&self, entry: &Entry) -> FileStats {
+        if self.needs_metadata() {
+            self.throttle();
+            FileStats::read_entry(&entry.dir_entry, entry.is_symlink)
+        } else {
+            FileStats::default()
+        }
+    }
+
+    /// Whether an entry `depth` directories below its root may match. Depth 0
+    /// is a direct child of the root.
+    fn in_depth_range(&self, depth: usize) -> bool {
+        self.options.min_depth.is_none_or(|min| depth >= min)
+            && self.options.max_depth.is_none_or(|max| depth <= max)
+    }
+
+    /// How much `prefer_prefix` adds to a match of `file_name`, from
+    /// [`PREFIX_BONUS`] when the first matched character opens the name down
+    /// to nothing at its end. Matches starting in the directory get nothing.
+    ///
+    /// `name_start` is the character index the name starts at in the matched
+    /// text.
+    fn prefix_bonus(&self, indices: &[usize], name_start: usize, file_name: &str) -> i64 {
+        if !self.options.prefer_prefix {
+            return 0;
+        }
+
+        let Some(first) = indices
+            .first()
+            .and_then(|first| first.checked_sub(name_start))
+        else {
+            return 0;
+        };
+        let name_len = file_name.chars().count().max(1);
+
+        PREFIX_BONUS * name_len.saturating_sub(first) as i64 / name_len as i64
+    }
+
+    fn meets_min_score(&self, score: i64) -> bool {
+        self.options
+            .min_score
+            .is_none_or(|min_score| score >= min_score)
+    }
+
+    /// Whether the matched characters at `indices` are close enough together
+    /// for `max_gap`. Matches without indices always are.
+    fn is_compact(&self, indices: &[usize]) -> bool {
+        let Some(max_gap) = self.options.max_gap else {
+            return true;
+        };
 
-            matches.push((score, formatted_string));
+        match (indices.first(), indices.last()) {
+            (Some(first), Some(last)) => last - first + 1 - indices.len() <= max_gap,
+            _ => true,
         }
     }
 
-    fn search_directory(&self, path: &Path) -> anyhow::Result<()> {
-        let Ok(children) = std::fs::read_dir(path) else {
-            if self.verbose {
-                println!("Error reading directory: {:?}", path);
+    fn has_allowed_extension(&self, path: &Path, is_dir: bool) -> bool {
+        if self.extensions.is_empty() {
+            return true;
+        }
+
+        if is_dir {
+            return false;
+        }
+
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| self.extensions.contains(&extension.to_lowercase()))
+    }
+
+    /// Splits `path` into its file name, its path relative to `root` and that
+    /// path's parent, which is empty for entries directly inside `root`.
+    /// Returns `None` for paths outside `root` or without a file name.
+    ///
+    /// The root itself is relative to itself as `.`.
+    fn split_path<'p>(root: &Root, path: &'p Path) -> Option<(String, &'p Path, &'p Path)> {
+        let file_name = path.file_name()?.to_string_lossy().to_string();
+        let relative_path = match path.strip_prefix(&root.path).ok()? {
+            relative_path if relative_path.as_os_str().is_empty() => Path::new("."),
+            relative_path => relative_path,
+        };
+        let parent_dir = relative_path.parent().unwrap_or(Path::new(""));
+
+        Some((file_name, relative_path, parent_dir))
+    }
+
+    /// How many directories below its root `relative_path` is.
+    fn depth(relative_path: &Path) -> usize {
+        relative_path.components().count().saturating_sub(1)
+    }
+
+    fn check_content_match(&self, sender: &Sender<Vec<Match>>, root: &Root, entry: &Entry) {
+        let path = entry.path.as_path();
+        let is_dir = entry.is_dir;
+
+        if is_dir || !self.in_depth_range(entry.depth) || !self.has_allowed_extension(path, is_dir)
+        {
+            return;
+        }
+
+        let stats = FileStats::read_entry(&entry.dir_entry, entry.is_symlink);
+        let too_large = stats
+            .size
+            .is_none_or(|size| size > self.options.max_file_size);
+
+        if too_large || !self.in_size_range(is_dir, &stats) || !self.in_time_range(&stats) {
+            return;
+        }
+
+        let mut line_matches = match matching_lines(path, &self.matcher) {
+            Ok(line_matches) => line_matches,
+            Err(err) => {
+                self.record_error(path, Skipped::File, &err);
+                return;
             }
+        };
+
+        line_matches.retain(|line_match| {
+            self.meets_min_score(line_match.score) && self.is_compact(&line_match.indices)
+        });
+
+        if line_matches.is_empty() {
+            return;
+        }
 
-            return Ok(());
+        let Some((file_name, relative_path, parent_dir)) = Self::split_path(root, path) else {
+            return;
         };
+        let file_name = file_name.as_str();
 
-        children
-            .map(|entry| (entry.unwrap().path()))
-            .par_bridge()
-            .for_each(|path| {
-                let is_dir = path.is_dir();
+        let (base, parent_dir) = self.display_base(root, parent_dir);
+        let display_path = display_path(&base, parent_dir, file_name);
 
-                self.check_match(&path, is_dir);
+        let mut found = Vec::with_capacity(line_matches.len());
 
-                if is_dir {
-                    self.search_directory(&path).unwrap();
+        for line_match in line_matches {
+            let prefix = format!("{}:{}:", display_path, line_match.line_number);
+
+            let m = Match {
+                score: line_match.score,
+                root: root.path.to_string_lossy().to_string(),
+                relative_path: relative_path.to_string_lossy().to_string(),
+                depth: Self::depth(relative_path),
+                file_name: file_name.to_string(),
+                indices: line_match.indices,
+                line_number: Some(line_match.line_number),
+                full_path: path.to_path_buf(),
+                modified: stats.modified,
+                size: stats.size,
+                is_dir: false,
+                path: format!("{}{}", prefix, line_match.line),
+                highlight_start: prefix.len(),
+                line: Some(line_match.line),
+                archive_entry: None,
+                line_count: None,
+                symlink_target: None,
+            };
+
+            found.push(m);
+        }
+
+        self.send_matches(sender, found);
+    }
+
+    /// Lists the archive at `path`, `depth` directories below `root`, and
+    /// matches the files inside it like entries of a directory named after
+    /// the archive. Archives that can't be listed are recorded and skipped,
+    /// as are tarballs larger than `max_file_size`, since listing them reads
+    /// them whole.
+    fn check_archive_match(
+        &self,
+        sender: &Sender<Vec<Match>>,
+        root: &Root,
+        path: &Path,
+        depth: usize,
+    ) {
+        if lists_whole_archive(path) {
+            self.throttle();
+            let too_large = std::fs::metadata(path)
+                .is_ok_and(|metadata| metadata.len() > self.options.max_file_size);
+
+            if too_large {
+                return;
+            }
+        }
+
+        let entries = match archive_entries(path) {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.record_error(path, Skipped::Archive, &err);
+                return;
+            }
+        };
+
+        // Large archives list many entries, so stopping is checked for each
+        let found = entries
+            .into_iter()
+            .take_while(|_| !self.should_stop())
+            .filter_map(|entry| self.match_archive_entry(root, path, entry, depth))
+            .collect();
+
+        self.send_matches(sender, found);
+    }
+
+    /// Matches a file inside the archive at `archive`. Its size and
+    /// modification time aren't known, so those filters never pass.
+    fn match_archive_entry(
+        &self,
+        root: &Root,
+        archive: &Path,
+        entry: String,
+        depth: usize,
+    ) -> Option<Match> {
+        let path = archive.join(&entry);
+        let depth = depth + entry.split('/').count();
+
+        if !self.options.entry_type.allows(false)
+            || !self.in_depth_range(depth)
+            || !self.has_allowed_extension(&path, false)
+            || self.has_size_filter()
+            || self.has_time_filter()
+            || self.is_hidden(&path)
+            || self.is_excluded(root, &path)
+        {
+            return None;
+        }
+
+        let mut m = self.match_entry(&self.matcher, root, &path, false, FileStats::default())?;
+
+        // Both end in the entry's path after the archive's, the separator
+        // between them becomes the `!`
+        for text in [&mut m.path, &mut m.relative_path] {
+            let separator = text.len() - entry.len() - 1;
+            text.replace_range(separator..separator + 1, "!");
+        }
+        m.full_path = archive.to_path_buf();
+        m.archive_entry = Some(entry);
+
+        Some(m)
+    }
+
+    /// Where the display path of an entry in `parent_dir` below `root` starts,
+    /// and what's left of `parent_dir` to join onto it.
+    ///
+    /// That's the root's label unless paths are shown absolute, or relative
+    /// to the current directory when they're under it. Canonicalizing
+    /// resolves the whole directory but keeps the entry's own name, so the
+    /// matched text still ends the path.
+    fn display_base<'p>(&self, root: &Root, parent_dir: &'p Path) -> (PathBuf, &'p Path) {
+        if !self.options.absolute {
+            let Some(cwd) = &self.cwd else {
+                return (root.label.clone(), parent_dir);
+            };
+
+            let dir = root.path.join(parent_dir);
+            if let Ok(relative_dir) = dir.strip_prefix(cwd) {
+                return (Path::new(".").join(relative_dir), Path::new(""));
+            }
+        }
+
+        if self.options.canonicalize {
+            if let Ok(dir) = root.path.join(parent_dir).canonicalize() {
+                return (dir, Path::new(""));
+            }
+        }
+
+        (root.path.clone(), parent_dir)
+    }
+
+    /// The display path of `root` itself, shown like an entry named
+    /// `file_name` in the directory above it so the matched name still ends
+    /// the path, e.g. `../myapp` for a single root.
+    fn root_display_path(&self, root: &Root, file_name: &str) -> String {
+        let Some(parent) = root.path.parent() else {
+            return root.path.display().to_string();
+        };
+
+        // A label ending in `..` has no parent to show, so the absolute one
+        // is shown instead
+        let label = if root.label == Path::new(".") {
+            PathBuf::from("..")
+        } else if root.label.file_name().is_some() {
+            root.label.parent().unwrap_or(parent).to_path_buf()
+        } else {
+            parent.to_path_buf()
+        };
+        let above = Root {
+            path: parent.to_path_buf(),
+            label,
+            device: None,
+        };
+
+        let (base, parent_dir) = self.display_base(&above, Path::new(""));
+        display_path(&base, parent_dir, file_name)
+    }
+
+    fn check_match(&self, root: &Root, entry: &Entry) -> Option<Match> {
+        let path = entry.path.as_path();
+        let is_dir = entry.is_dir;
+
+        if !self.options.entry_type.allows(is_dir)
+            || !self.in_depth_range(entry.depth)
+            || !self.has_allowed_extension(path, is_dir)
+            || !self.is_same_file(entry)
+        {
+            return None;
+        }
+
+        let stats = self.file_stats(entry);
+        if !self.in_size_range(is_dir, &stats) || !self.in_time_range(&stats) {
+            return None;
+        }
+
+        let mut m = self.match_entry(&self.matcher, root, path, is_dir, stats)?;
+
+        if self.options.line_counts && !is_dir {
+            // Files that can't be read are still matched, just not counted
+            m.line_count = count_lines(path, self.options.max_file_size).ok().flatten();
+        }
+
+        if self.options.symlink_targets && entry.is_symlink {
+            self.throttle();
+            m.symlink_target = Some(Self::symlink_target(path));
+        }
+
+        Some(m)
+    }
+
+    /// Whether `entry` is another name of `options.same_file`, always true
+    /// without one. Symlinks and directories are never hard links, and
+    /// entries that can't be opened are skipped.
+    fn is_same_file(&self, entry: &Entry) -> bool {
+        let Some(same_file) = &self.same_file else {
+            return true;
+        };
+
+        if entry.is_dir || entry.is_symlink {
+            return false;
+        }
+
+        self.throttle();
+        Handle::from_path(&entry.path).is_ok_and(|handle| handle == *same_file)
+    }
+
+    /// Where the symlink at `path` points, or `<broken>` when that doesn't
+    /// exist or the link can't be read.
+    fn symlink_target(path: &Path) -> String {
+        match std::fs::read_link(path) {
+            Ok(target) if path.exists() => target.display().to_string(),
+            _ => "<broken>".to_string(),
+        }
+    }
+
+    /// Matches the root directory itself with `include_root`, like an entry
+    /// above every other. Contents aren't searched for it, and with a
+    /// `min_depth` it's too shallow to match.
+    fn check_root_match(&self, root: &Root) -> Option<Match> {
+        if !self.options.include_root
+            || self.options.same_file.is_some()
+            || self.options.search_contents
+            || !self.options.entry_type.allows(true)
+            || self.options.min_depth.is_some_and(|min| min > 0)
+            || !self.has_allowed_extension(&root.path, true)
+        {
+            return None;
+        }
+
+        let stats = if self.needs_metadata() {
+            self.throttle();
+            FileStats::read(&root.path)
+        } else {
+            FileStats::default()
+        };
+        if !self.in_size_range(true, &stats) || !self.in_time_range(&stats) {
+            return None;
+        }
+
+        self.match_entry(&self.matcher, root, &root.path, true, stats)
+    }
+
+    /// Matches a single entry with `matcher`. Filters are left to the caller
+    /// and the file system is only touched to canonicalize the display path.
+    fn match_entry(
+        &self,
+        matcher: &Matcher,
+        root: &Root,
+        path: &Path,
+        is_dir: bool,
+        stats: FileStats,
+    ) -> Option<Match> {
+        let (file_name, relative_path, parent_dir) = Self::split_path(root, path)?;
+        let file_name = file_name.as_str();
+
+        // The root has no path of its own relative to itself, so only its
+        // name is matched
+        let is_root = path == root.path;
+        let whole_path = (self.options.match_path || self.options.path_terms) && !is_root;
+        let display_text = if whole_path {
+            slash_path(relative_path)
+        } else {
+            file_name.to_string()
+        };
+
+        // Leaving the extension off the end keeps every index valid for the
+        // displayed text
+        let extension_len = if self.options.stem {
+            file_name.len() - Path::new(file_name).file_stem().map_or(0, OsStr::len)
+        } else {
+            0
+        };
+        let match_text = &display_text[..display_text.len() - extension_len];
+
+        let found = if self.options.path_terms {
+            let name = &file_name[..file_name.len() - extension_len];
+            matcher.fmatch_components(&slash_path(parent_dir), name)
+        } else {
+            matcher.fmatch(match_text)
+        };
+        let (score, indices) = found
+            .map(|(score, indices)| {
+                let name_start = display_text.chars().count() - file_name.chars().count();
+                let bonus = self.prefix_bonus(&indices, name_start, file_name);
+                (score + bonus, indices)
+            })
+            .filter(|(score, indices)| self.meets_min_score(*score) && self.is_compact(indices))?;
+
+        let full_path = path.to_path_buf();
+        let path = if is_root {
+            self.root_display_path(root, file_name)
+        } else if whole_path {
+            let (base, _) = self.display_base(root, Path::new(""));
+            display_slash_path(&base, &display_text)
+        } else {
+            let (base, parent_dir) = self.display_base(root, parent_dir);
+            display_path(&base, parent_dir, file_name)
+        };
+        // The matched text always ends the display path
+        let highlight_start = path.len() - display_text.len();
+
+        Some(Match {
+            score,
+            root: root.path.to_string_lossy().to_string(),
+            relative_path: relative_path.to_string_lossy().to_string(),
+            depth: Self::depth(relative_path),
+            file_name: file_name.to_string(),
+            indices,
+            line_number: None,
+            line: None,
+            archive_entry: None,
+            line_count: None,
+            symlink_target: None,
+            full_path,
+            modified: stats.modified,
+            size: stats.size,
+            is_dir,
+            path,
+            highlight_start,
+        })
+    }
+
+    /// Matches and ranks `paths` like a search over exactly these entries
+    /// would, applying the same filters, `limit`, `dedup` and `max_per_dir`.
+    /// The file system is only read for the size and time filters and sort
+    /// orders that need metadata, and to canonicalize display paths.
+    ///
+    /// Paths ending in a separator are treated as directories and paths
+    /// outside every root are matched as they are. If the
+    /// [`cancel_flag`](Self::cancel_flag) is set ranking stops early and
+    /// returns the matches found so far.
+    pub fn rank<I: IntoIterator<Item = PathBuf>>(&self, paths: I) -> Vec<Match> {
+        self.rank_with(&self.matcher, paths)
+    }
+
+    /// Like [`rank`](Self::rank), but matching with `matcher` instead of the
+    /// searcher's query, to rank the same paths against changing queries.
+    pub fn rank_with<I: IntoIterator<Item = PathBuf>>(
+        &self,
+        matcher: &Matcher,
+        paths: I,
+    ) -> Vec<Match> {
+        let _running = self.running();
+        let unrooted = Root {
+            path: PathBuf::new(),
+            label: PathBuf::from("."),
+            device: None,
+        };
+
+        let mut matches = paths
+            .into_iter()
+            // Stops reading long inputs on Ctrl-C like the traversal does
+            .take_while(|_| !self.should_stop())
+            .filter_map(|path| {
+                let is_dir = path
+                    .as_os_str()
+                    .as_encoded_bytes()
+                    .last()
+                    .is_some_and(|byte| std::path::is_separator(*byte as char));
+                let root = self
+                    .roots
+                    .iter()
+                    .find(|root| path.starts_with(&root.path))
+                    .unwrap_or(&unrooted);
+
+                let depth = path.strip_prefix(&root.path).map_or(0, Self::depth);
+
+                if !self.options.entry_type.allows(is_dir)
+                    || !self.in_depth_range(depth)
+                    || !self.has_allowed_extension(&path, is_dir)
+                    || self.is_hidden_below(root, &path)
+                    || self.is_excluded(root, &path)
+                {
+                    return None;
+                }
+
+                let stats = if self.needs_metadata() {
+                    self.throttle();
+                    FileStats::read(&path)
+                } else {
+                    FileStats::default()
+                };
+                if !self.in_size_range(is_dir, &stats) || !self.in_time_range(&stats) {
+                    return None;
                 }
-            });
 
-        anyhow::Ok(())
+                self.match_entry(matcher, root, &path, is_dir, stats)
+            })
+            // Like a search stopping once it found as many
+            .take(self.options.limit.map_or(usize::MAX, NonZeroUsize::get))
+            .collect::<Vec<Match>>();
+
+        if self.options.dedup {
+            dedup_matches(&mut matches);
+        }
+        if let Some(max) = self.options.max_per_dir {
+            cap_matches_per_dir(&mut matches, max.get());
+        }
+        sort_matches(&mut matches, self.options.sort_by);
+
+        matches
     }
 
-    fn show_all(&self) {
-        let matches = self.matches.lock().unwrap();
-        let matches = matches
-            .iter()
-            .map(|(_, path)| path.to_string())
-            .collect::<Vec<String>>();
+    /// Sends a batch of matches, such as those of one directory, to the
+    /// collector. Only as many as fit under the limit are sent.
+    fn send_matches(&self, sender: &Sender<Vec<Match>>, mut batch: Vec<Match>) {
+        if batch.is_empty() {
+            return;
+        }
 
-        println!();
+        let limit = self.options.limit.map_or(usize::MAX, NonZeroUsize::get);
+        let found = self.found.fetch_add(batch.len(), Ordering::Relaxed);
 
-        clear_screen();
+        if found.saturating_add(batch.len()) >= limit {
+            self.limit_reached.store(true, Ordering::Relaxed);
+            batch.truncate(limit.saturating_sub(found));
+        }
 
-        println!("All Matches ({}):", matches.len());
-        println!("{}", matches.join("\n"));
+        // The collector only stops once every sender is dropped, so this
+        // can't fail
+        if !batch.is_empty() {
+            let _ = sender.send(batch);
+        }
     }
 
-    fn filter(&self) {
-        let query = Text::new("Filter by:").prompt().unwrap();
-        let matches = self.matches.lock().unwrap();
+    /// Receives matches from the workers until all of them are done, adding
+    /// every batch that's waiting under a single lock.
+    fn collect_matches(&self, receiver: Receiver<Vec<Match>>) {
+        // The real files already streamed, with `dedup`
+        let mut streamed = HashSet::new();
 
-        let matches = matches
-            .iter()
-            .filter(|(_, path)| path.contains(&query))
-            .map(|(_, path)| path.to_string())
-            .collect::<Vec<String>>();
+        while let Ok(batch) = receiver.recv() {
+            let mut matches = self.matches.lock().unwrap();
 
-            println!();
+            for m in std::iter::once(batch).chain(receiver.try_iter()).flatten() {
+                self.stream_match(&m, &mut streamed);
+                matches.push(m);
+            }
+        }
+    }
 
-        clear_screen();
+    /// Prints a match as soon as it's found when streaming. Only the
+    /// collector calls this, which keeps lines from interleaving.
+    ///
+    /// With `dedup` only the first match of each real file is printed, as
+    /// the best scoring one isn't known yet.
+    fn stream_match(&self, m: &Match, streamed: &mut HashSet<DedupKey>) {
+        if !self.options.stream {
+            return;
+        }
+
+        if self.options.dedup && !streamed.insert(dedup_key(m)) {
+            return;
+        }
 
-        println!("Filtered Matches ({}):", matches.len());
-        println!("{}", matches.join("\n"));
+        // Stop searching once nothing is reading the output anymore, e.g.
+        // when piped into `head`
+        let line = m.plain(self.options.format.as_ref());
+        if write!(stdout().lock(), "{}{}", line, self.options.terminator()).is_err() {
+            self.cancelled.store(true, Ordering::Relaxed);
+        }
     }
 
-    fn after_search(&self) -> anyhow::Result<()> {
-        let answer = Select::new("Options:", AfterSearchOption::VARIANTS.to_vec()).prompt()?;
-        let answer = AfterSearchOption::from_str(answer).unwrap();
+    pub(super) fn is_ignored(&self, path: &Path, is_dir: bool, ignores: &IgnoreChain) -> bool {
+        if !self.options.respect_gitignore {
+            return false;
+        }
 
-        match answer {
-            AfterSearchOption::ShowAll => self.show_all(),
-            AfterSearchOption::Filter => self.filter(),
+        if is_dir && path.file_name().is_some_and(|name| name == ".git") {
+            return true;
         }
 
-        Ok(())
+        ignores.is_ignored(path, is_dir)
     }
 
-    pub fn search(&self, path: &Path) -> anyhow::Result<()> {
+    fn is_hidden(&self, path: &Path) -> bool {
+        if self.options.hidden {
+            return false;
+        }
+
+        path.file_name()
+            .is_some_and(|name| name.as_encoded_bytes().starts_with(b"."))
+    }
+
+    /// Whether any component of `path` below `root` is hidden. The traversal
+    /// never enters hidden directories, so it only has to check the name.
+    pub(super) fn is_hidden_below(&self, root: &Root, path: &Path) -> bool {
+        if self.options.hidden {
+            return false;
+        }
+
+        path.strip_prefix(&root.path)
+            .unwrap_or(path)
+            .components()
+            .any(|component| match component {
+                Component::Normal(name) => name.as_encoded_bytes().starts_with(b"."),
+                _ => false,
+            })
+    }
+
+    pub(super) fn is_excluded(&self, root: &Root, path: &Path) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+
+        match path.strip_prefix(&root.path) {
+            Ok(relative_path) => self.exclude.is_match(relative_path),
+            Err(_) => false,
+        }
+    }
+
+    /// Records `path` as visited, returning false if it was already searched.
+    ///
+    /// Only tracked when following symlinks, since without them the tree
+    /// can't contain cycles.
+    fn first_visit(&self, path: &Path) -> bool {
+        if !self.options.follow_symlinks {
+            return true;
+        }
+
+        let Ok(canonical) = path.canonicalize() else {
+            return false;
+        };
+
+        self.visited.lock().unwrap().insert(canonical)
+    }
+
+    /// Whether `dir` is on the same device as `root`, always true unless
+    /// `one_file_system` recorded the root's device.
+    fn on_root_device(root: &Root, dir: &Path) -> bool {
+        root.device
+            .is_none_or(|device| device_id(dir) == Some(device))
+    }
+
+    /// Checks every entry of `path` and queues its subdirectories on `scope`.
+    ///
+    /// Directories are searched as separate tasks on the searcher's thread
+    /// pool rather than by recursing, so deep trees never grow the stack or
+    /// oversubscribe the pool.
+    fn search_directory<'s>(
+        &'s self,
+        scope: &Scope<'s>,
+        sender: &'s Sender<Vec<Match>>,
+        root: &'s Root,
+        path: &Path,
+        ignores: &IgnoreChain,
+        depth: usize,
+    ) {
+        if self.should_stop() {
+            return;
+        }
+
+        self.throttle();
+        let children = match std::fs::read_dir(path) {
+            Ok(children) => children,
+            Err(err) => {
+                self.record_error(path, Skipped::Directory, &err);
+                return;
+            }
+        };
+
+        // Sent together once the whole directory is checked, rather than one
+        // message per match
+        let mut found = Vec::new();
+
+        for entry in children {
+            if self.should_stop() {
+                break;
+            }
+
+            let file_type = entry.and_then(|entry| Ok((entry.file_type()?, entry)));
+            let (file_type, dir_entry) = match file_type {
+                Ok(file_type) => file_type,
+                Err(err) => {
+                    self.record_error(path, Skipped::Entry, &err);
+                    continue;
+                }
+            };
+
+            self.scanned.fetch_add(1, Ordering::Relaxed);
+
+            // The listing usually knows the file type already, only symlinks
+            // need a stat to find out what they point to
+            let is_symlink = file_type.is_symlink();
+            let path = dir_entry.path();
+            let is_dir = if is_symlink {
+                self.throttle();
+                path.is_dir()
+            } else {
+                file_type.is_dir()
+            };
+
+            if self.is_hidden(&path)
+                || self.is_excluded(root, &path)
+                || self.is_ignored(&path, is_dir, ignores)
+            {
+                continue;
+            }
+
+            let entry = Entry {
+                dir_entry,
+                path,
+                is_dir,
+                is_symlink,
+                depth,
+            };
+
+            let can_descend = self.options.max_depth.is_none_or(|max| depth < max)
+                && (self.options.follow_symlinks || !is_symlink);
+            let path = (is_dir && can_descend)
+                .then(|| entry.path.clone())
+                .filter(|path| Self::on_root_device(root, path));
+
+            if self.options.search_archives
+                && !self.options.search_contents
+                && !is_dir
+                && is_archive(&entry.path)
+            {
+                let path = entry.path.clone();
+                scope.spawn(move |_| self.check_archive_match(sender, root, &path, depth));
+            }
+
+            if !self.options.search_contents {
+                found.extend(self.check_match(root, &entry));
+            } else if !is_dir {
+                // Reading files is the expensive part, so spread it out too
+                scope.spawn(move |_| self.check_content_match(sender, root, &entry));
+            }
+
+            if let Some(path) = path.filter(|path| self.first_visit(path)) {
+                let ignores = if self.options.respect_gitignore {
+                    let mut errors = Vec::new();
+                    let ignores = ignores.descend(&path, &mut errors);
+                    self.record_ignore_errors(errors);
+                    ignores
+                } else {
+                    ignores.clone()
+                };
+
+                scope.spawn(move |scope| {
+                    self.search_directory(scope, sender, root, &path, &ignores, depth + 1)
+                });
+            }
+        }
+
+        self.send_matches(sender, found);
+    }
+
+    /// The style matches are highlighted with when printed, `None` when colors
+    /// are disabled.
+    pub(super) fn render_style(&self) -> Option<MatchStyle> {
+        self.options
+            .color
+            .enabled()
+            .then_some(self.options.match_style)
+    }
+
+    /// Searches every root without printing anything or prompting the user.
+    ///
+    /// Returns every match in the configured sort order, best score first by
+    /// default. Use [`Match::render`] to format one for display. If the
+    /// [`cancel_flag`](Self::cancel_flag) is set the search stops early and
+    /// returns the matches found so far.
+    pub fn collect(&self) -> anyhow::Result<Vec<Match>> {
+        let _running = self.running();
+
+        self.matches.lock().unwrap().clear();
+        self.found.store(0, Ordering::Relaxed);
+        self.visited.lock().unwrap().clear();
+        self.scanned.store(0, Ordering::Relaxed);
+        self.limit_reached.store(false, Ordering::Relaxed);
+        self.timed_out.store(false, Ordering::Relaxed);
+        self.errors.lock().unwrap().clear();
+
+        let (done, finished) = mpsc::channel::<()>();
+
+        thread::scope(|threads| {
+            // Flags the traversal to stop unless it finishes, dropping `done`,
+            // before the timeout
+            if let Some(timeout) = self.options.timeout {
+                threads.spawn(move || {
+                    if finished.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout) {
+                        self.timed_out.store(true, Ordering::Relaxed);
+                    }
+                });
+            }
+
+            let (sender, receiver) = mpsc::channel();
+            threads.spawn(move || self.collect_matches(receiver));
+
+            self.pool.scope(|scope| {
+                let sender = &sender;
+
+                for root in &self.roots {
+                    self.first_visit(&root.path);
+                    if let Some(m) = self.check_root_match(root) {
+                        self.send_matches(sender, vec![m]);
+                    }
+
+                    let ignores = if self.options.respect_gitignore {
+                        let mut errors = Vec::new();
+                        let ignores = IgnoreChain::new(&root.path, &mut errors);
+                        self.record_ignore_errors(errors);
+                        ignores
+                    } else {
+                        IgnoreChain::default()
+                    };
+
+                    scope.spawn(move |scope| {
+                        self.search_directory(scope, sender, root, &root.path, &ignores, 0)
+                    });
+                }
+            });
+
+            // Lets the collector finish once it has received every match
+            drop(sender);
+            drop(done);
+        });
+
+        let mut matches = self.matches.lock().unwrap();
+        if self.options.dedup {
+            dedup_matches(&mut matches);
+        }
+        if let Some(max) = self.options.max_per_dir {
+            cap_matches_per_dir(&mut matches, max.get());
+        }
+        sort_matches(&mut matches, self.options.sort_by);
+
+        Ok(matches.clone())
+    }
+
+    /// The `top` best matches of the last search rendered for display, and
+    /// how many more there are. Both are taken from the same matches that
+    /// Show All, Filter and the other after-search options work on.
+    pub(super) fn top_matches(&self) -> (Vec<String>, usize) {
+        let mut matches = self.matches.lock().unwrap();
+
+        get_top_matches(
+            &mut matches,
+            self.options.top,
+            self.options.sort_by,
+            self.render_style(),
+            self.options.show_scores,
+        )
+    }
+
+    pub fn search(&self) -> anyhow::Result<()> {
         let start = std::time::Instant::now();
 
         let matches = Arc::clone(&self.matches);
         let last_printed = Arc::clone(&self.last_printed);
+        let top = self.options.top;
+        let sort_by = self.options.sort_by;
+        let style = self.render_style();
+        let show_scores = self.options.show_scores;
+        let scanned = Arc::clone(&self.scanned);
 
         let completed_search = Arc::new(Mutex::new(false));
         let completed_search_clone = Arc::clone(&completed_search);
 
-        thread::spawn(move || {
-            let mut last_printed = last_printed.lock().unwrap();
+        let preview = thread::spawn(move || {
+            let mut last_len = None;
+            let mut last_height = None;
+
+            // The best `top` matches so far. Matches are only ever pushed, so
+            // only those added since the last redraw need to be ranked
+            // instead of copying and sorting all of them every time
+            let mut best: Vec<Match> = Vec::new();
+
+            let progress = ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("{spinner} {elapsed} {msg}")
+                    .unwrap()
+                    .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ "),
+            );
+            progress.enable_steady_tick(PREVIEW_INTERVAL);
 
             loop {
-                let matches_ref = matches.lock().unwrap();
-                let mut matches = matches_ref.clone();
-                let completed_search_clone = completed_search_clone.lock().unwrap();
+                thread::sleep(PREVIEW_INTERVAL);
 
-                if *completed_search_clone {
+                if *completed_search_clone.lock().unwrap() {
                     break;
                 }
 
+                let matches_ref = matches.lock().unwrap();
+                let total = matches_ref.len();
+                let added = matches_ref
+                    .get(last_len.unwrap_or(0)..)
+                    .unwrap_or_default()
+                    .to_vec();
                 drop(matches_ref);
 
-                let (matches, extra_matches) = get_top_matches(&mut matches);
+                // Re-read every time so a resized window is redrawn to fit
+                let height = terminal_height();
 
-                if matches == *last_printed {
-                    print!("\r... {} more matches", extra_matches);
-                    continue;
+                // An unchanged length means there is nothing new to draw
+                if last_len != Some(total) || last_height != height {
+                    last_len = Some(total);
+                    last_height = height;
+
+                    best.extend(added);
+                    sort_matches(&mut best, sort_by);
+                    best.truncate(top);
+
+                    // Leaves a row for the spinner, and one for the overflow
+                    // count when the window can't fit every match
+                    let rows = height.map_or(top, |rows| rows.saturating_sub(1));
+                    let shown = if rows < top {
+                        rows.saturating_sub(1).max(1)
+                    } else {
+                        top
+                    };
+
+                    let mut matches =
+                        render_matches(&best[..best.len().min(shown)], style.as_ref(), show_scores);
+                    let extra_matches = total - matches.len();
+
+                    if shown < top && extra_matches > 0 {
+                        matches.push(format!("... {} more matches", format_count(extra_matches)));
+                    }
+
+                    // Only held for this redraw so other readers are never
+                    // blocked for the lifetime of the preview
+                    let mut last_printed = last_printed.lock().unwrap();
+
+                    if matches != *last_printed {
+                        // Hides the spinner while redrawing so it's never
+                        // torn by the screen clear
+                        progress.suspend(|| {
+                            clear_screen();
+                            eprintln!("{}", matches.join("\n"));
+                        });
+
+                        *last_printed = matches;
+                    }
                 }
 
-                clear_screen();
-                println!("{}", matches.join("\n"));
+                let scanned = scanned.load(Ordering::Relaxed);
+                let per_second = scanned as f64 / start.elapsed().as_secs_f64().max(0.001);
 
-                *last_printed = matches;
+                progress.set_message(format!(
+                    "scanned {} entries ({}/s), {} matches",
+                    format_count(scanned),
+                    format_count(per_second as usize),
+                    format_count(total)
+                ));
             }
+
+            progress.finish_and_clear();
         });
 
-        self.search_directory(path).unwrap();
+        self.collect()?;
         *completed_search.lock().unwrap() = true;
+        preview.join().unwrap();
 
-        let matches_ref = self.matches.lock().unwrap();
-        let (matches, extra_matches) = get_top_matches(&mut matches_ref.clone());
+        let (matches, extra_matches) = self.top_matches();
+        *self.last_printed.lock().unwrap() = matches.clone();
 
         clear_screen();
 
-        println!("{}", matches.join("\n"));
-        println!(
-            "... {} more matches in {:?}\n",
-            extra_matches,
-            start.elapsed()
+        eprintln!("{}", matches.join("\n"));
+        self.report_errors();
+        self.report_warnings();
+        eprintln!(
+            "... {} more matches in {:?}, scanned {} entries{}\n",
+            format_count(extra_matches),
+            start.elapsed(),
+            format_count(self.scanned.load(Ordering::Relaxed)),
+            if self.is_cancelled() {
+                " (interrupted)"
+            } else if self.timed_out() {
+                " (timed out, results are partial)"
+            } else if self.limit_reached.load(Ordering::Relaxed) {
+                " (limit reached)"
+            } else {
+                ""
+            }
         );
 
-        drop(matches_ref);
+        if let Some(output) = &self.options.output {
+            let written = self.write_matches(output)?;
+            eprintln!(
+                "Wrote {} matches to {}\n",
+                format_count(written),
+                output.display()
+            );
+        }
 
         self.after_search()?;
 
         Ok(())
     }
+
+    /// Writes the plain paths of the last search's matches to `output`, one
+    /// per line or NUL separated with `print0`, returning how many were
+    /// written.
+    pub fn write_matches(&self, output: &Path) -> anyhow::Result<usize> {
+        let matches = self.matches.lock().unwrap();
+
+        let mut contents = String::new();
+        for m in matches.iter() {
+            contents.push_str(&m.plain(self.options.format.as_ref()));
+            contents.push(self.options.terminator());
+        }
+
+        std::fs::write(output, contents)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+
+        Ok(matches.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::MAIN_SEPARATOR_STR};
+
+    use super::*;
+
+    fn search(dir: &Path, query: &str, options: SearchOptions) -> Vec<Match> {
+        let searcher =
+            Searcher::with_options(dir.to_path_buf(), query.to_string(), options).unwrap();
+
+        searcher.collect().unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn self_referential_symlink_terminates() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), "").unwrap();
+        std::os::unix::fs::symlink(".", dir.path().join("a")).unwrap();
+
+        let options = SearchOptions {
+            follow_symlinks: true,
+            ..SearchOptions::default()
+        };
+        let matches = search(dir.path(), "file", options);
+
+        let paths = matches
+            .iter()
+            .map(|m| m.relative_path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(paths, ["file.txt"]);
+    }
+
+    #[test]
+    fn invalid_ignore_rule_keeps_the_valid_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".gitignore"), "secret.txt\n{a\n").unwrap();
+        fs::write(dir.path().join("secret.txt"), "").unwrap();
+        fs::write(dir.path().join("public.txt"), "").unwrap();
+
+        let searcher = Searcher::builder()
+            .query("txt")
+            .root(dir.path())
+            .build()
+            .unwrap();
+        let matches = searcher.collect().unwrap();
+
+        let paths = matches
+            .iter()
+            .map(|m| m.relative_path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(paths, ["public.txt"]);
+
+        let errors = searcher.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].skipped, Skipped::IgnoreRule);
+    }
+
+    #[test]
+    fn tar_archives_over_the_max_file_size_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut archive = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(0);
+        header.set_cksum();
+        archive
+            .append_data(&mut header, "notes.txt", std::io::empty())
+            .unwrap();
+        fs::write(dir.path().join("logs.tar"), archive.into_inner().unwrap()).unwrap();
+
+        let search = |max_file_size| {
+            search(
+                dir.path(),
+                "notes",
+                SearchOptions {
+                    search_archives: true,
+                    max_file_size,
+                    ..SearchOptions::default()
+                },
+            )
+        };
+
+        assert_eq!(search(u64::MAX).len(), 1);
+        assert!(search(1).is_empty());
+    }
+
+    #[test]
+    fn higher_min_score_keeps_fewer_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["main.rs", "domain.rs", "my_archive_index.txt"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let search = |min_score| {
+            let options = SearchOptions {
+                min_score: Some(min_score),
+                ..SearchOptions::default()
+            };
+
+            search(dir.path(), "main", options)
+        };
+
+        let low = search(0);
+        let high = search(low[0].score);
+
+        assert_eq!(low.len(), 3);
+        assert!(high.len() < low.len());
+        assert!(high.iter().all(|m| m.score >= low[0].score));
+    }
+
+    #[test]
+    fn file_directly_in_the_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), "").unwrap();
+
+        let matches = search(dir.path(), "main", SearchOptions::default());
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].relative_path, "main.rs");
+        assert_eq!(matches[0].file_name, "main.rs");
+        assert_eq!(matches[0].path, format!(".{}main.rs", MAIN_SEPARATOR_STR));
+    }
+
+    #[test]
+    fn root_is_shown_relative_like_other_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("myapp");
+        fs::create_dir(&root).unwrap();
+
+        let options = SearchOptions {
+            include_root: true,
+            ..SearchOptions::default()
+        };
+        let matches = search(&root, "myapp", options);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, format!("..{}myapp", MAIN_SEPARATOR_STR));
+    }
+
+    #[test]
+    fn file_directly_in_a_dot_root() {
+        let root = Root {
+            path: PathBuf::from("."),
+            label: PathBuf::from("."),
+            device: None,
+        };
+
+        let (file_name, relative_path, parent_dir) =
+            Searcher::split_path(&root, Path::new("./main.rs")).unwrap();
+
+        assert_eq!(file_name, "main.rs");
+        assert_eq!(relative_path, Path::new("main.rs"));
+        assert_eq!(parent_dir, Path::new(""));
+        assert!(Searcher::split_path(&root, Path::new("../main.rs")).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dedup_collapses_a_symlinked_duplicate() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("real")).unwrap();
+        fs::write(dir.path().join("real/notes.txt"), "").unwrap();
+        std::os::unix::fs::symlink("real/notes.txt", dir.path().join("notes.txt")).unwrap();
+
+        let search = |dedup| {
+            let options = SearchOptions {
+                dedup,
+                ..SearchOptions::default()
+            };
+
+            search(dir.path(), "notes", options)
+        };
+
+        assert_eq!(search(false).len(), 2);
+        assert_eq!(search(true).len(), 1);
+    }
+
+    #[test]
+    fn summary_counts_every_match() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..5 {
+            fs::write(dir.path().join(format!("notes{}.txt", i)), "").unwrap();
+        }
+
+        let options = SearchOptions {
+            top: 2,
+            ..SearchOptions::default()
+        };
+        let searcher =
+            Searcher::with_options(dir.path().to_path_buf(), "notes".to_string(), options).unwrap();
+        let total = searcher.collect().unwrap().len();
+
+        let (shown, extra) = searcher.top_matches();
+
+        assert_eq!(total, 5);
+        assert_eq!(shown.len(), 2);
+        assert_eq!(shown.len() + extra, total);
+    }
 }
@@ -0,0 +1,198 @@
+use std::{num::NonZeroUsize, path::PathBuf};
+
+use crate::matcher::matcher::{Algorithm, CaseMode, MatchMode};
+
+use super::{
+    search_options::{EntryType, SearchOptions},
+    searcher::Searcher,
+    top_matches::SortBy,
+};
+
+/// Configures a [`Searcher`] one option at a time, e.g.
+/// `Searcher::builder().query("main").max_depth(3).build()`.
+///
+/// Options without a dedicated method can be set through
+/// [`options`](Self::options). Searches the current directory unless a root
+/// is added.
+#[derive(Debug, Clone, Default)]
+pub struct SearcherBuilder {
+    query: Option<String>,
+    roots: Vec<PathBuf>,
+    options: SearchOptions,
+}
+
+impl SearcherBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The query to match entries against. Required.
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    /// Adds a directory to search, can be called more than once.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    /// Replaces every option, keeping the query and roots.
+    pub fn options(mut self, options: SearchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.options.verbose = verbose;
+        self
+    }
+
+    pub fn respect_gitignore(mut self, respect_gitignore: bool) -> Self {
+        self.options.respect_gitignore = respect_gitignore;
+        self
+    }
+
+    pub fn match_mode(mut self, match_mode: MatchMode) -> Self {
+        self.options.match_mode = match_mode;
+        self
+    }
+
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.options.algorithm = algorithm;
+        self
+    }
+
+    pub fn case(mut self, case: CaseMode) -> Self {
+        self.options.case = case;
+        self
+    }
+
+    /// Shorthand for [`case`](Self::case) with exact-case or smart-case.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.options.case = if case_sensitive {
+            CaseMode::Sensitive
+        } else {
+            CaseMode::Smart
+        };
+        self
+    }
+
+    pub fn fold(mut self, fold: bool) -> Self {
+        self.options.fold = fold;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.options.min_depth = Some(min_depth);
+        self
+    }
+
+    /// Adds a term matches must not contain, can be called more than once.
+    pub fn negate(mut self, term: impl Into<String>) -> Self {
+        self.options.negated.push(term.into());
+        self
+    }
+
+    /// Adds a glob of paths to skip, can be called more than once.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.options.exclude.push(pattern.into());
+        self
+    }
+
+    /// Adds a file of exclude globs, can be called more than once.
+    pub fn ignore_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.ignore_files.push(path.into());
+        self
+    }
+
+    pub fn search_contents(mut self, search_contents: bool) -> Self {
+        self.options.search_contents = search_contents;
+        self
+    }
+
+    /// Only matches the hard links of `path` instead of names matching the
+    /// query.
+    pub fn same_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.same_file = Some(path.into());
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.options.threads = Some(threads);
+        self
+    }
+
+    /// Performs at most `per_second` directory listings and stats per
+    /// second.
+    pub fn throttle(mut self, per_second: u32) -> Self {
+        self.options.throttle = Some(per_second);
+        self
+    }
+
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.options.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    pub fn min_score(mut self, min_score: i64) -> Self {
+        self.options.min_score = Some(min_score);
+        self
+    }
+
+    pub fn max_gap(mut self, max_gap: usize) -> Self {
+        self.options.max_gap = Some(max_gap);
+        self
+    }
+
+    pub fn limit(mut self, limit: NonZeroUsize) -> Self {
+        self.options.limit = Some(limit);
+        self
+    }
+
+    pub fn sort_by(mut self, sort_by: SortBy) -> Self {
+        self.options.sort_by = sort_by;
+        self
+    }
+
+    pub fn entry_type(mut self, entry_type: EntryType) -> Self {
+        self.options.entry_type = entry_type;
+        self
+    }
+
+    /// Adds an allowed file extension, can be called more than once.
+    pub fn extension(mut self, extension: impl Into<String>) -> Self {
+        self.options.extensions.push(extension.into());
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.options.hidden = hidden;
+        self
+    }
+
+    pub fn match_path(mut self, match_path: bool) -> Self {
+        self.options.match_path = match_path;
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<Searcher> {
+        let Some(query) = self.query else {
+            anyhow::bail!("A query is required to build a searcher");
+        };
+
+        let roots = if self.roots.is_empty() {
+            vec![std::env::current_dir()?]
+        } else {
+            self.roots
+        };
+
+        Searcher::with_roots(roots, query, self.options)
+    }
+}
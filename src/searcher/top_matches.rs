@@ -1,15 +1,200 @@
-use std::cmp::min;
+use std::{
+    cmp::{min, Ordering, Reverse},
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
-pub fn get_top_matches(matches: &mut [(i64, String)]) -> (Vec<String>, usize) {
-    let slice_index = min(matches.len(), 10);
-    let extra_matches = matches.len() - slice_index;
+use serde::Deserialize;
+use strum_macros::EnumString;
 
-    matches.sort_by(|a, b| b.0.cmp(&a.0));
+use crate::utils::match_style::MatchStyle;
 
-    let matches: Vec<String> = matches[0..slice_index]
+use super::search_match::Match;
+
+/// The order matches are ranked in. Ties are always broken by score.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SortBy {
+    /// Best score first.
+    #[default]
+    Score,
+    /// Alphabetically by relative path.
+    Path,
+    /// Most recently modified first.
+    Mtime,
+    /// Least recently modified first. Entries without a modification time
+    /// come last.
+    Oldest,
+    /// Largest first.
+    Size,
+    /// Shallowest first.
+    Depth,
+}
+
+impl SortBy {
+    /// Whether this order needs the file's metadata recorded during the search.
+    pub fn needs_metadata(self) -> bool {
+        matches!(self, SortBy::Mtime | SortBy::Oldest | SortBy::Size)
+    }
+}
+
+pub fn sort_matches(matches: &mut [Match], sort_by: SortBy) {
+    matches.sort_by(|a, b| {
+        let ordering = match sort_by {
+            SortBy::Score => Ordering::Equal,
+            SortBy::Path => a.relative_path.cmp(&b.relative_path),
+            SortBy::Mtime => Reverse(a.modified.unwrap_or(SystemTime::UNIX_EPOCH))
+                .cmp(&Reverse(b.modified.unwrap_or(SystemTime::UNIX_EPOCH))),
+            SortBy::Oldest => a
+                .modified
+                .is_none()
+                .cmp(&b.modified.is_none())
+                .then_with(|| a.modified.cmp(&b.modified)),
+            SortBy::Size => Reverse(a.size).cmp(&Reverse(b.size)),
+            SortBy::Depth => a.depth.cmp(&b.depth),
+        };
+
+        ordering.then_with(|| b.score.cmp(&a.score))
+    });
+}
+
+/// Keeps the best scoring match of each real file, dropping the others
+/// reached through symlinks or overlapping roots. Content matches are kept
+/// once per line and archive matches once per entry.
+pub fn dedup_matches(matches: &mut Vec<Match>) {
+    matches.sort_by_key(|m| Reverse(m.score));
+
+    let mut seen = HashSet::new();
+    matches.retain(|m| seen.insert(dedup_key(m)));
+}
+
+/// What [`dedup_matches`] tells matches apart by: the real path of their
+/// file, their line and their archive entry.
+pub type DedupKey = (PathBuf, Option<usize>, Option<String>);
+
+pub fn dedup_key(m: &Match) -> DedupKey {
+    let real_path = m
+        .full_path
+        .canonicalize()
+        .unwrap_or_else(|_| m.full_path.clone());
+
+    (real_path, m.line_number, m.archive_entry.clone())
+}
+
+/// Keeps the `max` best scoring matches of each directory, dropping the rest.
+/// Content matches count towards the directory of their file.
+pub fn cap_matches_per_dir(matches: &mut Vec<Match>, max: usize) {
+    matches.sort_by_key(|m| Reverse(m.score));
+
+    let mut counts = HashMap::new();
+    matches.retain(|m| {
+        let count = counts
+            .entry(m.full_path.parent().map(Path::to_path_buf))
+            .or_insert(0);
+        *count += 1;
+
+        *count <= max
+    });
+}
+
+/// How wide the scores of `matches` are when printed, so they line up in a
+/// column.
+pub fn score_width<'a>(matches: impl IntoIterator<Item = &'a Match>) -> usize {
+    matches
+        .into_iter()
+        .map(|m| m.score.to_string().len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Renders every match with `style`, prefixed by its score when `show_scores`
+/// is set.
+pub fn render_matches(
+    matches: &[Match],
+    style: Option<&MatchStyle>,
+    show_scores: bool,
+) -> Vec<String> {
+    let width = score_width(matches);
+
+    matches
         .iter()
-        .map(|(_, s)| s.clone())
-        .collect();
+        .map(|m| {
+            if show_scores {
+                format!("{} {}", m.render_score(width, style), m.render(style))
+            } else {
+                m.render(style)
+            }
+        })
+        .collect()
+}
+
+/// Sorts `matches` and returns the `count` best rendered with `style`, along
+/// with how many more matches there are. Scores are shown before the paths
+/// with `show_scores`.
+///
+/// The returned lines and the extra count always add up to `matches.len()`.
+pub fn get_top_matches(
+    matches: &mut [Match],
+    count: usize,
+    sort_by: SortBy,
+    style: Option<MatchStyle>,
+    show_scores: bool,
+) -> (Vec<String>, usize) {
+    sort_matches(matches, sort_by);
+
+    let shown = render_matches(
+        &matches[..min(matches.len(), count)],
+        style.as_ref(),
+        show_scores,
+    );
+
+    // Counted from what was rendered so the two can never disagree
+    let extra_matches = matches.len() - shown.len();
+
+    (shown, extra_matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn test_match(score: i64) -> Match {
+        let name = format!("file{}.txt", score);
+
+        Match {
+            score,
+            root: "/root".to_string(),
+            relative_path: name.clone(),
+            depth: 0,
+            file_name: name.clone(),
+            indices: Vec::new(),
+            line_number: None,
+            line: None,
+            archive_entry: None,
+            line_count: None,
+            symlink_target: None,
+            full_path: PathBuf::from("/root").join(&name),
+            modified: None,
+            size: None,
+            is_dir: false,
+            path: format!("./{}", name),
+            highlight_start: 2,
+        }
+    }
+
+    #[test]
+    fn shown_and_extra_add_up_to_the_total() {
+        for (total, count) in [(0, 10), (3, 10), (10, 10), (11, 10), (25, 1), (5, 0)] {
+            let mut matches = (0..total).map(test_match).collect::<Vec<Match>>();
+
+            let (shown, extra) = get_top_matches(&mut matches, count, SortBy::Score, None, false);
 
-    (matches, extra_matches)
+            assert_eq!(shown.len(), min(total as usize, count));
+            assert_eq!(shown.len() + extra, total as usize);
+        }
+    }
 }
@@ -0,0 +1,93 @@
+use std::{collections::BTreeMap, fmt, io, path::PathBuf};
+
+/// What a [`TraversalError`] made the search skip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Skipped {
+    /// A directory that couldn't be listed.
+    Directory,
+    /// An entry of a directory listing.
+    Entry,
+    /// A file whose contents couldn't be read.
+    File,
+    /// An archive whose entries couldn't be listed, e.g. because it's
+    /// corrupt.
+    Archive,
+    /// A line of an ignore file that isn't a valid rule. The file's other
+    /// rules still apply.
+    IgnoreRule,
+}
+
+impl Skipped {
+    fn noun(self, count: usize) -> &'static str {
+        match (self, count) {
+            (Skipped::Directory, 1) => "directory",
+            (Skipped::Directory, _) => "directories",
+            (Skipped::Entry, 1) => "entry",
+            (Skipped::Entry, _) => "entries",
+            (Skipped::File, 1) => "file",
+            (Skipped::File, _) => "files",
+            (Skipped::Archive, 1) => "archive",
+            (Skipped::Archive, _) => "archives",
+            (Skipped::IgnoreRule, 1) => "ignore rule",
+            (Skipped::IgnoreRule, _) => "ignore rules",
+        }
+    }
+}
+
+/// A path the search skipped because it couldn't be read. For a
+/// [`Skipped::Entry`] it's the directory being listed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraversalError {
+    pub path: PathBuf,
+    pub skipped: Skipped,
+    pub kind: io::ErrorKind,
+    pub message: String,
+}
+
+impl TraversalError {
+    pub fn new(path: PathBuf, skipped: Skipped, err: &io::Error) -> Self {
+        Self {
+            path,
+            skipped,
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for TraversalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.skipped {
+            Skipped::Directory => "directory",
+            Skipped::Entry => "entry in",
+            Skipped::File => "file",
+            Skipped::Archive => "archive",
+            Skipped::IgnoreRule => "ignore rule in",
+        };
+
+        write!(
+            f,
+            "Couldn't read {} {}: {}",
+            what,
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+/// Counts `errors` by what was skipped and why, one line each such as
+/// `3 directories skipped (permission denied)`.
+pub fn summarize_errors(errors: &[TraversalError]) -> Vec<String> {
+    let mut counts: BTreeMap<(Skipped, io::ErrorKind), usize> = BTreeMap::new();
+
+    for error in errors {
+        *counts.entry((error.skipped, error.kind)).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .map(|((skipped, kind), count)| {
+            format!("{} {} skipped ({})", count, skipped.noun(count), kind)
+        })
+        .collect()
+}
@@ -0,0 +1,160 @@
+use std::{collections::BTreeMap, path::Path};
+
+use crate::utils::match_style::MatchStyle;
+
+use super::search_match::Match;
+
+/// A directory on the way to a match, or a match itself.
+#[derive(Default)]
+struct Node {
+    /// The match's rendered name, when the node is one.
+    rendered: Option<String>,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    fn insert(&mut self, components: &[String], rendered: String) {
+        match components.split_first() {
+            Some((first, rest)) => self
+                .children
+                .entry(first.clone())
+                .or_default()
+                .insert(rest, rendered),
+            None => self.rendered = Some(rendered),
+        }
+    }
+
+    /// The node's label and the node whose children are shown below it.
+    /// Directories that only lead to a single other directory are collapsed
+    /// into one `a/b/c` label.
+    fn collapse<'n>(&'n self, name: &str, style: Option<&MatchStyle>) -> (String, &'n Node) {
+        if let Some(rendered) = &self.rendered {
+            return (rendered.clone(), self);
+        }
+
+        let mut label = name.to_string();
+        let mut node = self;
+
+        while node.children.len() == 1 {
+            let (child_name, child) = node.children.iter().next().unwrap();
+            if child.rendered.is_some() {
+                break;
+            }
+
+            label = format!("{}/{}", label.trim_end_matches('/'), child_name);
+            node = child;
+        }
+
+        let label = match style {
+            Some(_) => MatchStyle::DIRECTORY.apply(&label),
+            None => label,
+        };
+
+        (label, node)
+    }
+
+    fn render_children(&self, indent: &str, style: Option<&MatchStyle>, lines: &mut Vec<String>) {
+        let count = self.children.len();
+
+        for (i, (name, child)) in self.children.iter().enumerate() {
+            let last = i + 1 == count;
+            let (label, shown) = child.collapse(name, style);
+
+            lines.push(format!(
+                "{}{}{}",
+                indent,
+                if last { "└── " } else { "├── " },
+                label
+            ));
+
+            let indent = format!("{}{}", indent, if last { "    " } else { "│   " });
+            shown.render_children(&indent, style, lines);
+        }
+    }
+}
+
+/// Lays `matches` out as an indented tree like `tree` prints, showing only
+/// the directories leading to a match, sorted by name. Each root, or the
+/// first directory of absolute paths, starts a tree of its own.
+///
+/// Matched names are highlighted with `style`, or plain when it's `None`.
+pub fn render_tree(matches: &[Match], style: Option<&MatchStyle>) -> Vec<String> {
+    let mut tree = Node::default();
+
+    for m in matches {
+        let dir = &m.path[..m.dir_len()];
+        let mut components = Path::new(dir)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<String>>();
+        components.push(m.file_name.clone());
+
+        tree.insert(&components, m.render_from(dir.len(), style));
+    }
+
+    let mut lines = Vec::new();
+
+    for (name, root) in &tree.children {
+        let (label, shown) = root.collapse(name, style);
+
+        lines.push(label);
+        shown.render_children("", style, &mut lines);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn test_match(path: &str) -> Match {
+        let file_name = path.rsplit('/').next().unwrap().to_string();
+
+        Match {
+            score: 0,
+            root: ".".to_string(),
+            relative_path: path.trim_start_matches("./").to_string(),
+            depth: path.matches('/').count() - 1,
+            highlight_start: path.len() - file_name.len(),
+            file_name,
+            indices: Vec::new(),
+            line_number: None,
+            line: None,
+            archive_entry: None,
+            line_count: None,
+            symlink_target: None,
+            full_path: PathBuf::from(path),
+            modified: None,
+            size: None,
+            is_dir: false,
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn single_child_directories_are_collapsed() {
+        let matches = [
+            "./src/main.rs",
+            "./a/b/c/x.rs",
+            "./README.md",
+            "./a/b/c/y.rs",
+        ]
+        .map(test_match);
+
+        assert_eq!(
+            render_tree(&matches, None),
+            [
+                ".",
+                "├── README.md",
+                "├── a/b/c",
+                "│   ├── x.rs",
+                "│   └── y.rs",
+                "└── src",
+                "    └── main.rs",
+            ]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::bail;
+
+/// A named group of extensions, like `rg --type`, that expands into the
+/// extension filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypePreset {
+    pub name: &'static str,
+    /// Extensions without leading dots.
+    pub extensions: &'static [&'static str],
+}
+
+impl TypePreset {
+    /// Every built-in preset, sorted by name.
+    pub const ALL: &'static [TypePreset] = &[
+        TypePreset::new("c", &["c", "h"]),
+        TypePreset::new("config", &["toml", "yaml", "yml", "json", "ini"]),
+        TypePreset::new("cpp", &["cpp", "cc", "cxx", "hpp", "hh", "hxx", "h"]),
+        TypePreset::new("go", &["go", "mod"]),
+        TypePreset::new("java", &["java", "kt", "gradle"]),
+        TypePreset::new("js", &["js", "jsx", "mjs", "cjs"]),
+        TypePreset::new("markdown", &["md", "markdown"]),
+        TypePreset::new("py", &["py", "pyi"]),
+        TypePreset::new("rust", &["rs", "toml"]),
+        TypePreset::new("shell", &["sh", "bash", "zsh", "fish"]),
+        TypePreset::new("ts", &["ts", "tsx", "mts", "cts"]),
+        TypePreset::new("web", &["html", "css", "js", "ts"]),
+    ];
+
+    const fn new(name: &'static str, extensions: &'static [&'static str]) -> Self {
+        Self { name, extensions }
+    }
+}
+
+/// Formatted as `name: ext, ext`, how `--type-list` prints it.
+impl fmt::Display for TypePreset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.extensions.join(", "))
+    }
+}
+
+impl FromStr for TypePreset {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> anyhow::Result<Self> {
+        match Self::ALL
+            .iter()
+            .find(|preset| preset.name.eq_ignore_ascii_case(name))
+        {
+            Some(preset) => Ok(*preset),
+            None => bail!("Unknown type preset `{}`, see --type-list", name),
+        }
+    }
+}
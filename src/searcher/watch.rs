@@ -0,0 +1,206 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::Ordering,
+        mpsc::{self, RecvTimeoutError},
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use notify::{
+    event::{EventKind, ModifyKind},
+    Event, RecursiveMode, Watcher,
+};
+
+use crate::utils::{clear_screen::clear_screen, format_count::format_count};
+
+use super::{
+    ignore_chain::IgnoreChain,
+    searcher::{Root, Searcher, PREVIEW_INTERVAL},
+};
+
+/// How long the file system has to stay unchanged before `watch` searches
+/// again.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+impl Searcher {
+    /// Searches, then keeps the top matches on screen and searches again
+    /// whenever entries under the roots are created, removed or renamed,
+    /// until the [`cancel_flag`](Self::cancel_flag) is set. Changes the
+    /// search wouldn't see, in hidden, excluded or ignored directories such
+    /// as `.git` or `target`, are left alone.
+    ///
+    /// Bursts of changes, such as a checkout or a build, are gathered into a
+    /// single search once they settle for [`WATCH_DEBOUNCE`].
+    pub fn watch(&self) -> anyhow::Result<()> {
+        let search_contents = self.options.search_contents;
+        let (changed, changes) = mpsc::channel::<Vec<PathBuf>>();
+
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event {
+                if Self::changes_matches(&event.kind, search_contents) {
+                    let _ = changed.send(event.paths);
+                }
+            }
+        })
+        .context("Failed to watch for changes")?;
+
+        // Built once, ignore files below the roots are still read as the
+        // changed paths are checked
+        let root_ignores = self
+            .roots
+            .iter()
+            .map(|root| self.root_ignores(root))
+            .collect::<Vec<IgnoreChain>>();
+        let is_searched = |paths: &[PathBuf]| {
+            paths
+                .iter()
+                .any(|path| self.is_searched(path, &root_ignores))
+        };
+
+        for root in &self.roots {
+            watcher
+                .watch(&root.path, RecursiveMode::Recursive)
+                .with_context(|| format!("Failed to watch {}", root.path.display()))?;
+        }
+
+        // Ctrl-C stops watching while waiting for changes too
+        let _running = self.running();
+
+        loop {
+            let start = std::time::Instant::now();
+            self.collect()?;
+
+            let (matches, extra_matches) = self.top_matches();
+
+            clear_screen();
+
+            eprintln!("{}", matches.join("\n"));
+            self.report_errors();
+            self.report_warnings();
+            eprintln!(
+                "... {} more matches in {:?}, scanned {} entries, watching for changes (Ctrl-C to stop)",
+                format_count(extra_matches),
+                start.elapsed(),
+                format_count(self.scanned.load(Ordering::Relaxed)),
+            );
+
+            // Checks for Ctrl-C now and then while nothing changes
+            loop {
+                match changes.recv_timeout(PREVIEW_INTERVAL) {
+                    Ok(paths) if is_searched(&paths) => break,
+                    Ok(_) => {}
+                    Err(RecvTimeoutError::Timeout) if !self.is_cancelled() => {}
+                    Err(_) => return Ok(()),
+                }
+            }
+
+            // Only changes the search would see keep it waiting, so a busy
+            // ignored directory can't hold it off
+            let mut settled = Instant::now() + WATCH_DEBOUNCE;
+            while let Some(wait) = settled.checked_duration_since(Instant::now()) {
+                match changes.recv_timeout(wait) {
+                    Ok(paths) if is_searched(&paths) => settled = Instant::now() + WATCH_DEBOUNCE,
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+            }
+        }
+    }
+
+    /// The ignore rules of `root`, or none when they aren't respected.
+    fn root_ignores(&self, root: &Root) -> IgnoreChain {
+        if !self.options.respect_gitignore {
+            return IgnoreChain::default();
+        }
+
+        // Rules that can't be parsed are reported by every search
+        IgnoreChain::new(&root.path, &mut Vec::new())
+    }
+
+    /// Whether the search would reach `path`, with `root_ignores` the ignore
+    /// rules of each root. Neither it nor a directory above it below its root
+    /// may be hidden, excluded or ignored.
+    fn is_searched(&self, path: &Path, root_ignores: &[IgnoreChain]) -> bool {
+        let found = self
+            .roots
+            .iter()
+            .zip(root_ignores)
+            .find(|(root, _)| path.starts_with(&root.path));
+
+        // Paths reported in another form than the roots, e.g. with symlinks
+        // resolved, can't be told apart, so they're searched again
+        let Some((root, ignores)) = found else {
+            return true;
+        };
+
+        if self.is_hidden_below(root, path) {
+            return false;
+        }
+
+        let mut ignores = ignores.clone();
+        let mut entry = root.path.clone();
+
+        for component in path.strip_prefix(&root.path).unwrap().components() {
+            entry.push(component);
+
+            // Everything above `path` is a directory, `path` itself may be
+            // gone already
+            let is_dir = entry != path || path.is_dir();
+
+            if self.is_excluded(root, &entry) || self.is_ignored(&entry, is_dir, &ignores) {
+                return false;
+            }
+
+            if entry != path && self.options.respect_gitignore {
+                ignores = ignores.descend(&entry, &mut Vec::new());
+            }
+        }
+
+        true
+    }
+
+    /// Whether a change of `kind` can change which entries match: entries
+    /// appearing, going away or being renamed, or any write when contents
+    /// are searched.
+    fn changes_matches(kind: &EventKind, search_contents: bool) -> bool {
+        match kind {
+            EventKind::Create(_) | EventKind::Remove(_) => true,
+            EventKind::Modify(ModifyKind::Name(_)) => true,
+            EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Any) => search_contents,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::SearchOptions;
+
+    use super::*;
+
+    #[test]
+    fn changes_the_search_skips_are_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git/objects")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+
+        let searcher = Searcher::with_options(
+            dir.path().to_path_buf(),
+            "main".to_string(),
+            SearchOptions::default(),
+        )
+        .unwrap();
+        let root_ignores = [searcher.root_ignores(&searcher.roots[0])];
+        let root = &searcher.roots[0].path;
+
+        assert!(searcher.is_searched(&root.join("src/main.rs"), &root_ignores));
+        assert!(!searcher.is_searched(&root.join(".git/objects/ab"), &root_ignores));
+        assert!(!searcher.is_searched(&root.join("target/debug/main"), &root_ignores));
+    }
+}
@@ -1,3 +1,3 @@
 pub fn clear_screen() {
-    print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
+    eprint!("{esc}[2J{esc}[1;1H", esc = 27 as char);
 }
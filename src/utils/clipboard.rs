@@ -0,0 +1,25 @@
+use std::cell::RefCell;
+
+use anyhow::Context;
+use arboard::Clipboard;
+
+thread_local! {
+    /// Kept for as long as the process runs. On Linux the copied text is
+    /// served by whoever set it, so without a clipboard manager it would be
+    /// gone as soon as the last `Clipboard` is dropped.
+    static CLIPBOARD: RefCell<Option<Clipboard>> = const { RefCell::new(None) };
+}
+
+/// Replaces the contents of the system clipboard with `text`.
+pub fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    CLIPBOARD.with_borrow_mut(|clipboard| {
+        let clipboard = match clipboard {
+            Some(clipboard) => clipboard,
+            None => clipboard.insert(Clipboard::new().context("No system clipboard is available")?),
+        };
+
+        clipboard
+            .set_text(text)
+            .context("Failed to copy to the clipboard")
+    })
+}
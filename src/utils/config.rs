@@ -0,0 +1,45 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::searcher::search_options::SearchOptions;
+
+/// Where the config file is looked for when none is given,
+/// `$XDG_CONFIG_HOME/searcher/config.toml` or
+/// `~/.config/searcher/config.toml`.
+pub fn default_config_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+
+    Some(config_dir.join("searcher").join("config.toml"))
+}
+
+/// Reads the default options from the TOML config file at `path`, or from
+/// [`default_config_path`] when it's `None`. Options the file doesn't set
+/// keep their defaults.
+///
+/// Only a missing default config file is an empty config, a missing `path`
+/// is an error.
+pub fn load_config(path: Option<&Path>) -> anyhow::Result<SearchOptions> {
+    let (path, required) = match path {
+        Some(path) => (path.to_path_buf(), true),
+        None => match default_config_path() {
+            Some(path) => (path, false),
+            None => return Ok(SearchOptions::default()),
+        },
+    };
+
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound && !required => {
+            return Ok(SearchOptions::default())
+        }
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to read config {}", path.display()))
+        }
+    };
+
+    toml::from_str(&contents).with_context(|| format!("Failed to parse config {}", path.display()))
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+
+/// The id of the device `path` is on, following symlinks. On Windows that's
+/// the serial number of its volume. `None` when it can't be read, and always
+/// on platforms other than Unix and Windows.
+pub fn device_id(path: &Path) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        std::fs::metadata(path).ok().map(|metadata| metadata.dev())
+    }
+
+    #[cfg(windows)]
+    {
+        use winapi_util::{file, Handle};
+
+        let handle = Handle::from_path_any(path).ok()?;
+        file::information(&handle)
+            .ok()
+            .map(|information| information.volume_serial_number())
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Whether [`device_id`] can tell devices apart on this platform.
+pub const fn is_supported() -> bool {
+    cfg!(any(unix, windows))
+}
@@ -0,0 +1,31 @@
+use std::path::{Path, MAIN_SEPARATOR_STR};
+
+/// Formats `file_name` inside `parent_dir`, relative to `root`, using the
+/// platform's path separator. A `root` of `.` gives a `./` prefixed path.
+pub fn display_path(root: &Path, parent_dir: &Path, file_name: &str) -> String {
+    let mut path = root.to_path_buf();
+
+    if !parent_dir.as_os_str().is_empty() {
+        path.push(parent_dir);
+    }
+    path.push(file_name);
+
+    path.display().to_string()
+}
+
+/// Joins the components of `path` with `/` regardless of platform.
+pub fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Formats a path produced by [`slash_path`] like [`display_path`] does.
+pub fn display_slash_path(root: &Path, slash_path: &str) -> String {
+    display_path(
+        root,
+        Path::new(""),
+        &slash_path.replace('/', MAIN_SEPARATOR_STR),
+    )
+}
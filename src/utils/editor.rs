@@ -0,0 +1,35 @@
+use std::{path::Path, process::Command};
+
+use anyhow::{bail, Context};
+
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Opens `path` in `$EDITOR`, falling back to `$VISUAL` and then the
+/// platform's default editor, and waits for it to exit.
+///
+/// The editor variable may contain arguments, e.g. `code --wait`.
+pub fn open_in_editor(path: &Path) -> anyhow::Result<()> {
+    let editor = ["EDITOR", "VISUAL"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to launch editor `{}`", editor))?;
+
+    if !status.success() {
+        bail!("Editor `{}` exited with {}", editor, status);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::{io::ErrorKind, path::Path, process::Command};
+
+use anyhow::{bail, Context};
+
+/// Shows `path` in the platform's file manager, selected in Finder or
+/// Explorer, or by opening its directory with `xdg-open` elsewhere.
+///
+/// Returns `false` without an error when there's no file manager to launch,
+/// so the caller can fall back to printing the directory.
+pub fn reveal_in_file_manager(path: &Path) -> anyhow::Result<bool> {
+    let Some(mut command) = reveal_command(path) else {
+        return Ok(false);
+    };
+
+    let status = match command.status() {
+        Ok(status) => status,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!(
+                    "Failed to launch {}",
+                    command.get_program().to_string_lossy()
+                )
+            })
+        }
+    };
+
+    // Explorer exits with 1 even when it opened the window
+    if !status.success() && !cfg!(windows) {
+        bail!(
+            "{} exited with {}",
+            command.get_program().to_string_lossy(),
+            status
+        );
+    }
+
+    Ok(true)
+}
+
+#[cfg(target_os = "macos")]
+fn reveal_command(path: &Path) -> Option<Command> {
+    let mut command = Command::new("open");
+    command.arg("-R").arg(path);
+    Some(command)
+}
+
+#[cfg(windows)]
+fn reveal_command(path: &Path) -> Option<Command> {
+    let mut select = std::ffi::OsString::from("/select,");
+    select.push(path);
+
+    let mut command = Command::new("explorer");
+    command.arg(select);
+    Some(command)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn reveal_command(path: &Path) -> Option<Command> {
+    let mut command = Command::new("xdg-open");
+    command.arg(path.parent()?);
+    Some(command)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn reveal_command(_path: &Path) -> Option<Command> {
+    None
+}
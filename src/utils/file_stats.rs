@@ -0,0 +1,42 @@
+use std::{
+    fs::{DirEntry, Metadata},
+    path::Path,
+    time::SystemTime,
+};
+
+/// The parts of a file's metadata that matches can be sorted by.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileStats {
+    pub modified: Option<SystemTime>,
+    pub size: Option<u64>,
+}
+
+impl FileStats {
+    pub fn read(path: &Path) -> Self {
+        match path.metadata() {
+            Ok(metadata) => Self::from_metadata(&metadata),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Reads the stats of a directory entry, following symlinks like
+    /// [`read`](Self::read). Other entries use [`DirEntry::metadata`], which
+    /// avoids resolving the path again.
+    pub fn read_entry(entry: &DirEntry, is_symlink: bool) -> Self {
+        if is_symlink {
+            return Self::read(&entry.path());
+        }
+
+        match entry.metadata() {
+            Ok(metadata) => Self::from_metadata(&metadata),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        Self {
+            modified: metadata.modified().ok(),
+            size: Some(metadata.len()),
+        }
+    }
+}
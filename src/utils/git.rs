@@ -0,0 +1,44 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context};
+
+/// Lists the files git tracks below `dir`, joined onto `dir`.
+///
+/// Returns `None` when `dir` isn't inside a git work tree.
+pub fn git_tracked_files(dir: &Path) -> anyhow::Result<Option<Vec<PathBuf>>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["ls-files", "-z"])
+        // The stderr checked below is translated in other locales
+        .env("LC_ALL", "C")
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if stderr.contains("not a git repository") {
+            return Ok(None);
+        }
+
+        bail!(
+            "git ls-files failed in {}: {}",
+            dir.display(),
+            stderr.trim()
+        );
+    }
+
+    // Paths are NUL separated and never quoted with -z
+    let files = output
+        .stdout
+        .split(|&byte| byte == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| dir.join(&*String::from_utf8_lossy(path)))
+        .collect();
+
+    Ok(Some(files))
+}
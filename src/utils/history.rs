@@ -0,0 +1,75 @@
+use std::{fmt, path::Path, path::PathBuf};
+
+use anyhow::{bail, Context};
+use inquire::{InquireError, Select};
+use serde::{Deserialize, Serialize};
+
+/// How many searches the history keeps, most recent first.
+pub const HISTORY_LIMIT: usize = 50;
+
+/// A previous search, as recorded in the history file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub query: String,
+    pub roots: Vec<PathBuf>,
+}
+
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let roots = self
+            .roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect::<Vec<String>>();
+
+        write!(f, "{} in {}", self.query, roots.join(", "))
+    }
+}
+
+/// Reads the recorded searches, most recent first. A missing file is an
+/// empty history.
+pub fn load_history(path: &Path) -> anyhow::Result<Vec<HistoryEntry>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to read history {}", path.display()))
+        }
+    };
+
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse history {}", path.display()))
+}
+
+/// Moves `entry` to the front of the history, dropping any earlier copy of it
+/// and the oldest searches past [`HISTORY_LIMIT`].
+pub fn record_history(path: &Path, entry: HistoryEntry) -> anyhow::Result<()> {
+    let mut history = load_history(path)?;
+    history.retain(|previous| *previous != entry);
+    history.insert(0, entry);
+    history.truncate(HISTORY_LIMIT);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    std::fs::write(path, serde_json::to_string_pretty(&history)?)
+        .with_context(|| format!("Failed to write history {}", path.display()))
+}
+
+/// Lets the user pick one of the recorded searches, returning `None` if they
+/// cancel.
+pub fn pick_from_history(path: &Path) -> anyhow::Result<Option<HistoryEntry>> {
+    let history = load_history(path)?;
+
+    if history.is_empty() {
+        bail!("No searches recorded in {} yet", path.display());
+    }
+
+    match Select::new("Recent searches:", history).prompt() {
+        Ok(entry) => Ok(Some(entry)),
+        Err(InquireError::OperationCanceled) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
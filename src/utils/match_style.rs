@@ -0,0 +1,157 @@
+use std::{
+    io::{stderr, IsTerminal},
+    str::FromStr,
+};
+
+use anyhow::bail;
+use colored::Color;
+use serde::{Deserialize, Deserializer};
+use strum_macros::EnumString;
+
+/// Whether colorized output is printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Color only when stderr, which the interactive output is drawn on, is a
+    /// terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether output drawn on stderr is colored.
+    pub fn enabled(self) -> bool {
+        self.enabled_on(stderr().is_terminal())
+    }
+
+    /// Whether output is colored on a stream that `is_terminal` or not, such
+    /// as stdout.
+    pub fn enabled_on(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Auto => is_terminal && std::env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// How matched characters are highlighted.
+///
+/// Parsed from `+` separated parts such as `red+bold`, `blue+underline` or
+/// `bold`. `none` disables highlighting entirely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchStyle {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub underline: bool,
+}
+
+impl MatchStyle {
+    pub const NONE: MatchStyle = MatchStyle {
+        color: None,
+        bold: false,
+        underline: false,
+    };
+
+    /// How directories are shown, set apart from files.
+    pub const DIRECTORY: MatchStyle = MatchStyle {
+        color: Some(Color::Blue),
+        bold: true,
+        underline: false,
+    };
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    /// This style with `base`'s color and attributes where it has none.
+    pub fn over(&self, base: &MatchStyle) -> MatchStyle {
+        MatchStyle {
+            color: self.color.or(base.color),
+            bold: self.bold || base.bold,
+            underline: self.underline || base.underline,
+        }
+    }
+
+    /// Wraps `text` in the ANSI escape codes for this style.
+    ///
+    /// Only what the style set is reset afterwards, so a style without a
+    /// color keeps the text's own color and following text is unaffected.
+    ///
+    /// The codes are always emitted, whether they should be printed is
+    /// decided by [`ColorChoice`] at print time. A style setting nothing
+    /// leaves `text` as it is.
+    pub fn apply(&self, text: &str) -> String {
+        if self.is_none() {
+            return text.to_string();
+        }
+
+        let mut codes = Vec::new();
+        let mut resets = Vec::new();
+
+        if self.bold {
+            codes.push("1".into());
+            resets.push("22");
+        }
+        if self.underline {
+            codes.push("4".into());
+            resets.push("24");
+        }
+        if let Some(color) = self.color {
+            codes.push(color.to_fg_str());
+            resets.push("39");
+        }
+
+        format!(
+            "\x1b[{}m{}\x1b[{}m",
+            codes.join(";"),
+            text,
+            resets.join(";")
+        )
+    }
+}
+
+impl Default for MatchStyle {
+    fn default() -> Self {
+        Self {
+            color: Some(Color::Red),
+            bold: true,
+            underline: false,
+        }
+    }
+}
+
+/// Deserialized from the same text [`FromStr`] parses, e.g. `"green+bold"`.
+impl<'de> Deserialize<'de> for MatchStyle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromStr for MatchStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> anyhow::Result<Self> {
+        let mut style = Self::NONE;
+
+        for part in text.split('+').map(str::trim) {
+            match part.to_lowercase().as_str() {
+                "none" => return Ok(Self::NONE),
+                "bold" => style.bold = true,
+                "underline" => style.underline = true,
+                name => match Color::from_str(name) {
+                    Ok(color) if style.color.is_none() => style.color = Some(color),
+                    Ok(_) => bail!("Invalid match style `{}`, only one color is allowed", text),
+                    Err(()) => bail!("Invalid match style `{}`, unknown part `{}`", text, part),
+                },
+            }
+        }
+
+        Ok(style)
+    }
+}
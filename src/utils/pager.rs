@@ -0,0 +1,44 @@
+use console::{measure_text_width, Key, Term};
+
+/// Prints `header` and then `lines` on stderr a screenful at a time, waiting
+/// for space or enter before each next page and stopping early on `q` or
+/// escape.
+///
+/// Everything is printed at once when it fits or stderr isn't a terminal.
+pub fn page(header: &str, lines: &[String]) {
+    let term = Term::stderr();
+
+    let Some((rows, columns)) = term.size_checked() else {
+        eprintln!("{}", header);
+        eprintln!("{}", lines.join("\n"));
+        return;
+    };
+
+    // Long lines wrap onto several rows
+    let rows_of = |line: &String| measure_text_width(line).div_ceil(columns as usize).max(1);
+
+    // Leaves a row for the prompt
+    let page_rows = (rows as usize).saturating_sub(1).max(1);
+
+    eprintln!("{}", header);
+    let mut used = 1;
+
+    for (shown, line) in lines.iter().enumerate() {
+        let line_rows = rows_of(line);
+
+        if used + line_rows > page_rows && used > 0 {
+            eprint!("-- {}/{}, space for more, q to stop --", shown, lines.len());
+
+            let key = term.read_key();
+            let _ = term.clear_line();
+
+            match key {
+                Ok(Key::Char(' ') | Key::Enter | Key::ArrowDown | Key::PageDown) => used = 0,
+                _ => return,
+            }
+        }
+
+        eprintln!("{}", line);
+        used += line_rows;
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::{bail, Context};
+
+/// Parses a human readable size such as `500`, `10MB`, `1.5GiB` into bytes.
+///
+/// Decimal suffixes (`KB`, `MB`, ...) are powers of 1000 and binary suffixes
+/// (`KiB`, `MiB`, ...) powers of 1024. Suffixes are case-insensitive and a
+/// bare `K`, `M`, `G` or `T` is treated as decimal.
+pub fn parse_size(text: &str) -> anyhow::Result<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, suffix) = text.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("Invalid size `{}`", text))?;
+
+    let multiplier: u64 = match suffix.trim().to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000_u64.pow(2),
+        "g" | "gb" => 1000_u64.pow(3),
+        "t" | "tb" => 1000_u64.pow(4),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => bail!("Invalid size `{}`, unknown unit `{}`", text, suffix.trim()),
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_and_binary_units() {
+        assert_eq!(parse_size("500").unwrap(), 500);
+        assert_eq!(parse_size("10KB").unwrap(), 10_000);
+        assert_eq!(parse_size("10KiB").unwrap(), 10_240);
+        assert_eq!(parse_size("2M").unwrap(), 2_000_000);
+        assert_eq!(parse_size("1.5GiB").unwrap(), 1_610_612_736);
+    }
+
+    #[test]
+    fn units_ignore_case() {
+        assert_eq!(parse_size("10kb").unwrap(), 10_000);
+        assert_eq!(parse_size("10mib").unwrap(), 10 << 20);
+        assert_eq!(parse_size(" 3 Tb ").unwrap(), 3_000_000_000_000);
+    }
+
+    #[test]
+    fn invalid_sizes_are_rejected() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("-1").is_err());
+        assert!(parse_size("10XB").is_err());
+    }
+}
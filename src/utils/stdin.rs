@@ -0,0 +1,40 @@
+use std::io::{stdin, IsTerminal};
+
+/// Whether stdin is something paths can be piped in from: a file, a FIFO or
+/// a socket, like ripgrep decides.
+///
+/// A terminal or a character device such as `/dev/null`, which cron, CI and
+/// `</dev/null` leave stdin as, isn't, so searches from scripts still walk
+/// the file system.
+pub fn is_readable_stdin() -> bool {
+    if stdin().is_terminal() {
+        return false;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::{fd::AsFd, unix::fs::FileTypeExt};
+
+        let Ok(fd) = stdin().as_fd().try_clone_to_owned() else {
+            return false;
+        };
+        let Ok(metadata) = std::fs::File::from(fd).metadata() else {
+            return false;
+        };
+
+        let file_type = metadata.file_type();
+        file_type.is_file() || file_type.is_fifo() || file_type.is_socket()
+    }
+
+    #[cfg(windows)]
+    {
+        use winapi_util::{file, HandleRef};
+
+        file::typ(HandleRef::stdin()).is_ok_and(|typ| typ.is_disk() || typ.is_pipe())
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        false
+    }
+}
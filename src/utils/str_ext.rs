@@ -1,20 +1,52 @@
-use colored::Colorize;
+use super::match_style::MatchStyle;
 
 pub trait StrExt {
-    fn colorize_matches(&self, indices: Vec<usize>) -> String;
+    /// Styles the characters at `indices` with `style` and the others with
+    /// `rest`. Matched characters keep whatever of `rest` `style` doesn't
+    /// override.
+    fn colorize_matches_over(
+        &self,
+        indices: Vec<usize>,
+        style: &MatchStyle,
+        rest: &MatchStyle,
+    ) -> String;
 }
 
 impl StrExt for str {
-    fn colorize_matches(&self, indices: Vec<usize>) -> String {
-        self.chars()
-            .enumerate()
-            .map(|(i, c)| {
-                if indices.contains(&i) {
-                    c.to_string().red().bold().to_string()
-                } else {
-                    c.to_string().normal().to_string()
-                }
-            })
-            .collect()
+    fn colorize_matches_over(
+        &self,
+        indices: Vec<usize>,
+        style: &MatchStyle,
+        rest: &MatchStyle,
+    ) -> String {
+        if style.is_none() && rest.is_none() {
+            return self.to_string();
+        }
+
+        let matched_style = style.over(rest);
+        let mut colorized = String::with_capacity(self.len());
+        let mut run = String::new();
+        let mut run_matched = false;
+
+        // Consecutive matched, or unmatched, characters are styled as one run
+        for (i, c) in self.chars().enumerate() {
+            let matched = indices.contains(&i);
+
+            if matched != run_matched && !run.is_empty() {
+                let run_style = if run_matched { &matched_style } else { rest };
+                colorized.push_str(&run_style.apply(&run));
+                run.clear();
+            }
+
+            run_matched = matched;
+            run.push(c);
+        }
+
+        if !run.is_empty() {
+            let run_style = if run_matched { &matched_style } else { rest };
+            colorized.push_str(&run_style.apply(&run));
+        }
+
+        colorized
     }
 }
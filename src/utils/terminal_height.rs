@@ -0,0 +1,7 @@
+use console::Term;
+
+/// How many rows the terminal the interactive output is drawn on has, `None`
+/// when stderr isn't a terminal.
+pub fn terminal_height() -> Option<usize> {
+    Term::stderr().size_checked().map(|(rows, _)| rows as usize)
+}
@@ -0,0 +1,58 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A token bucket limiting how many operations run per second, shared by
+/// every worker.
+///
+/// Up to a second's worth of operations can run at once after a quiet
+/// period, after that each [`wait`](Self::wait) sleeps until its turn.
+pub struct Throttle {
+    per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// Operations that can run without waiting, negative when workers are
+    /// already waiting for their turn.
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl Throttle {
+    pub fn new(per_second: u32) -> Self {
+        let per_second = f64::from(per_second.max(1));
+
+        Self {
+            per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: per_second,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token, sleeping until one is available.
+    pub fn wait(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.per_second);
+            bucket.refilled = now;
+
+            // Taken up front, so workers waiting at the same time each
+            // reserve a later turn instead of all waking at once
+            bucket.tokens -= 1.0;
+            bucket.tokens
+        };
+
+        // Slept without holding the lock so other workers can reserve theirs
+        if wait < 0.0 {
+            thread::sleep(Duration::from_secs_f64(-wait / self.per_second));
+        }
+    }
+}
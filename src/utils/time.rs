@@ -0,0 +1,115 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context};
+
+/// Parses a duration such as `90s`, `30m`, `2h`, `7d` or `2w`.
+pub fn parse_duration(text: &str) -> anyhow::Result<Duration> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+
+    let number: u64 = number
+        .parse()
+        .with_context(|| format!("Invalid duration `{}`", text))?;
+
+    let seconds = match unit {
+        "ms" => return Ok(Duration::from_millis(number)),
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => bail!(
+            "Invalid duration `{}`, expected a unit of ms, s, m, h, d or w",
+            text
+        ),
+    };
+
+    let seconds = number
+        .checked_mul(seconds)
+        .with_context(|| format!("Duration `{}` is too long", text))?;
+
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Parses a point in time, either a duration ago such as `7d` or a UTC date
+/// formatted as `YYYY-MM-DD`.
+pub fn parse_time(text: &str) -> anyhow::Result<SystemTime> {
+    if let Some(date) = parse_date(text.trim()) {
+        return Ok(date);
+    }
+
+    let duration = parse_duration(text)
+        .with_context(|| format!("Invalid time `{}`, expected e.g. 7d or 2024-01-01", text))?;
+
+    SystemTime::now()
+        .checked_sub(duration)
+        .with_context(|| format!("Time `{}` is too far in the past", text))
+}
+
+fn parse_date(text: &str) -> Option<SystemTime> {
+    let mut parts = text.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+
+    let seconds = days_from_civil(year, month, day).checked_mul(24 * 60 * 60)?;
+
+    if seconds >= 0 {
+        SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+    } else {
+        SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+    }
+}
+
+/// How many days `month` has in `year`, with 29 in February of leap years.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflowing_duration_is_an_error() {
+        assert!(parse_duration("18446744073709551615w").is_err());
+        assert_eq!(
+            parse_duration("2w").unwrap(),
+            Duration::from_secs(1_209_600)
+        );
+    }
+
+    #[test]
+    fn dates_past_the_end_of_the_month_are_rejected() {
+        assert!(parse_date("2024-02-31").is_none());
+        assert!(parse_date("2023-02-29").is_none());
+        assert!(parse_date("2024-04-31").is_none());
+        assert!(parse_date("2024-02-29").is_some());
+    }
+}
@@ -0,0 +1,9 @@
+/// Prints a warning on stderr, as a `{"warning": ...}` object with --json so
+/// everything on stderr stays JSON.
+pub fn print_warning(message: &str, json: bool) {
+    if json {
+        eprintln!("{}", serde_json::json!({ "warning": message }));
+    } else {
+        eprintln!("Warning: {}", message);
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+fn searcher() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_searcher"))
+}
+
+/// Runs the searcher with `paths` piped in, one per line, from inside `dir`.
+fn rank_piped(dir: &std::path::Path, args: &[&str], paths: &str) -> String {
+    let mut child = searcher()
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Dropped right away so the searcher sees the end of its input
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(paths.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn null_stdin_searches_the_current_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), "").unwrap();
+
+    let output = searcher()
+        .args(["--count", "main"])
+        .current_dir(dir.path())
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n");
+}
+
+#[test]
+fn piped_stdin_is_ranked() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), "").unwrap();
+    fs::write(dir.path().join("other_main.rs"), "").unwrap();
+
+    assert_eq!(
+        rank_piped(dir.path(), &["--count", "main"], "main.rs\n"),
+        "1\n"
+    );
+}
+
+#[test]
+fn piped_paths_are_filtered_by_size() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+    let args = ["--count", "main", "--min-size", "10GB"];
+    assert_eq!(rank_piped(dir.path(), &args, "main.rs\n"), "0\n");
+
+    let args = ["--count", "main", "--max-size", "10GB"];
+    assert_eq!(rank_piped(dir.path(), &args, "main.rs\n"), "1\n");
+}
+
+#[test]
+fn piped_paths_are_limited() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let args = ["--count", "main", "--limit", "1"];
+    assert_eq!(rank_piped(dir.path(), &args, "main.rs\nmain.c\n"), "1\n");
+}
+
+#[test]
+fn piped_paths_inside_hidden_directories_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = ".github/workflows/main.yml\nsrc/main.rs\n";
+
+    assert_eq!(rank_piped(dir.path(), &["--count", "main"], paths), "1\n");
+    assert_eq!(
+        rank_piped(dir.path(), &["--count", "main", "--hidden"], paths),
+        "2\n"
+    );
+}
+
+#[test]
+fn warnings_are_json_with_json() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.txt"), "").unwrap();
+
+    let output = searcher()
+        .args(["a", "--json"])
+        .arg(dir.path())
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.is_empty());
+    for line in stderr.lines() {
+        let warning: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(warning["warning"].is_string());
+    }
+}
+
+#[test]
+fn config_rejects_a_zero_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    fs::write(&config, "limit = 0\n").unwrap();
+
+    let output = searcher()
+        .args(["a", "--count", "--config"])
+        .arg(&config)
+        .arg(dir.path())
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn config_print0_separates_with_nul() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("notes.txt"), "").unwrap();
+    let config = dir.path().join("config.toml");
+    fs::write(&config, "print0 = true\n").unwrap();
+
+    let output = searcher()
+        .args(["notes", "--first", "--config"])
+        .arg(&config)
+        .arg(dir.path())
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    assert!(output.stdout.ends_with(b"\0"));
+}
+
+#[test]
+fn git_tracked_outside_a_repository_falls_back_in_any_locale() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+    let output = searcher()
+        .args(["notes", "--count", "--git-tracked"])
+        .arg(dir.path())
+        .env("LC_ALL", "de_DE.UTF-8")
+        .env("LANGUAGE", "de")
+        .env("GIT_CEILING_DIRECTORIES", dir.path().parent().unwrap())
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), "1");
+}
+
+#[test]
+fn single_character_warning_is_only_for_fuzzy_queries() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.txt"), "").unwrap();
+
+    let stderr = |mode: Option<&str>| {
+        let output = searcher()
+            .args(["a", "--count"])
+            .args(mode)
+            .arg(dir.path())
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    assert!(stderr(None).contains("single character"));
+    assert!(!stderr(Some("--regex")).contains("single character"));
+}
+
+#[cfg(unix)]
+#[test]
+fn streamed_matches_are_deduplicated() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("real")).unwrap();
+    fs::write(dir.path().join("real/notes.txt"), "").unwrap();
+    std::os::unix::fs::symlink("real/notes.txt", dir.path().join("notes.txt")).unwrap();
+
+    let output = searcher()
+        .args(["notes", "--stream", "--dedup"])
+        .arg(dir.path())
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 1);
+}